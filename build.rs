@@ -0,0 +1,43 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// Writes the list of files compiled into the game when the `embed-assets` feature is on
+fn main() {
+    println!("cargo:rerun-if-changed=assets");
+
+    let mut files = Vec::new();
+    if env::var_os("CARGO_FEATURE_EMBED_ASSETS").is_some() {
+        let root = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap()).join("assets");
+        collect(&root, &root, &mut files);
+        files.sort();
+    }
+
+    let mut out = String::from("pub static ASSETS: &[(&str, &[u8])] = &[\n");
+    for (name, path) in files {
+        out += &format!("    ({:?}, include_bytes!({:?})),\n", name, path);
+    }
+    out += "];\n";
+
+    let dest = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_assets.rs");
+    fs::write(dest, out).unwrap();
+}
+
+fn collect(root: &Path, dir: &Path, files: &mut Vec<(String, String)>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect(root, &path, files);
+        } else {
+            let name = path
+                .strip_prefix(root)
+                .unwrap()
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((name, path.display().to_string()));
+        }
+    }
+}
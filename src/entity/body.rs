@@ -0,0 +1,145 @@
+use std::{cmp::Ordering, time::Duration};
+
+use speedy2d::{shape::Rectangle, Graphics2D};
+
+use crate::{
+    screen::{
+        camera::Camera,
+        game::{self, DRAG},
+    },
+    utility::animator::Animator,
+    world::{collision::GameRect, space::GamePos, World},
+};
+
+use super::{
+    health::Health,
+    reaction::{HitReaction, Recovery},
+    Facing,
+};
+
+/// What the player and goblins share: a box that walks, falls, lands on the terrain and gets knocked about
+pub struct Body {
+    pub pos: GamePos,
+    pub velocity: GamePos,
+    pub health: Health,
+    size: (f32, f32),
+    /// Acceleration while walking, in units per second squared
+    speed: f32,
+    /// Position before the last update, for smoothing between updates when drawing
+    prev_pos: GamePos,
+    /// Direction the body walks in during the next update
+    thrust: GamePos,
+    /// Velocity change waiting for the next update
+    impulse: GamePos,
+    recovery: Recovery,
+    /// Whether the last update ended standing on solid ground
+    on_ground: bool,
+}
+
+impl Body {
+    pub fn new(size: (f32, f32), speed: f32, health: u32, reaction: HitReaction) -> Body {
+        Body {
+            pos: (0.0, 0.0).into(),
+            velocity: (0.0, 0.0).into(),
+            health: Health::new(health),
+            size,
+            speed,
+            prev_pos: (0.0, 0.0).into(),
+            thrust: (0.0, 0.0).into(),
+            impulse: (0.0, 0.0).into(),
+            recovery: Recovery::new(reaction),
+            on_ground: false,
+        }
+    }
+    /// Walks, falls and slides along the terrain for `dt`
+    pub fn update(&mut self, dt: Duration, world: &World) {
+        self.recovery.update(dt);
+        let dt = dt.as_secs_f32();
+        self.prev_pos = self.pos;
+
+        self.velocity += self.thrust * self.speed * dt + self.impulse;
+        self.thrust = (0.0, 0.0).into();
+        self.impulse = (0.0, 0.0).into();
+        self.velocity.y += game::GRAVITY * dt;
+        self.velocity *= (-DRAG * dt).exp();
+        let sweep = world.terrain.sweep(self.bounds(), self.velocity * dt);
+        self.pos += sweep.moved;
+        if sweep.hit_x {
+            self.velocity.x = 0.0;
+        }
+        if sweep.hit_y {
+            self.velocity.y = 0.0;
+        }
+        self.on_ground = sweep.landed();
+    }
+    /// Draws `anim` over the body, `alpha` of the way along its last move
+    pub fn draw(&self, anim: &Animator, graphics: &mut Graphics2D, camera: &Camera, alpha: f32) {
+        let pos = self.render_pos(alpha);
+        anim.draw(
+            graphics,
+            Rectangle::from_tuples(
+                camera.game_to_pix(pos),
+                camera.game_to_pix((pos.x + self.size.0, pos.y + self.size.1).into()),
+            ),
+            self.recovery.tint(),
+        );
+    }
+    /// Puts the body at `pos` without it moving there
+    pub fn place(&mut self, pos: GamePos) {
+        self.pos = pos;
+        self.prev_pos = pos;
+    }
+    /// The area the sprite is drawn over
+    pub fn bounds(&self) -> GameRect {
+        GameRect::new(self.pos, self.size)
+    }
+    /// Where the body is drawn, `alpha` of the way from its previous position to its current one
+    pub fn render_pos(&self, alpha: f32) -> GamePos {
+        self.prev_pos.lerp(self.pos, alpha)
+    }
+    /// Walks in `direction` during the next update
+    pub fn walk(&mut self, direction: GamePos) {
+        self.thrust += direction;
+    }
+    /// Changes the velocity by `vector`, in units per second, at the next update
+    pub fn push(&mut self, vector: GamePos) {
+        self.impulse += vector;
+    }
+    /// Takes `damage` from an attack coming from `from`, and gets knocked away from it
+    ///
+    /// Hits while still invulnerable from the last one are ignored, and return false
+    pub fn take_hit(&mut self, damage: u32, from: GamePos) -> bool {
+        if !self.recovery.hit() {
+            return false;
+        }
+        self.health.damage(damage);
+        let away = if from.x > self.bounds().center().x { -1.0 } else { 1.0 };
+        let knockback = self.recovery.knockback();
+        self.push((away * knockback, -knockback / 2.0).into());
+        true
+    }
+    pub fn is_stunned(&self) -> bool {
+        self.recovery.is_stunned()
+    }
+    /// Whether an attack lands this step,
+    /// which is only on the frame that fires the hit event, and never while stunned
+    pub fn strikes(&self, anim: &Animator) -> bool {
+        !self.is_stunned() && anim.events().iter().any(|event| event == "hit")
+    }
+    /// Whether the last update ended standing on solid ground
+    pub fn on_ground(&self) -> bool {
+        self.on_ground
+    }
+    /// The way the body is moving, or `facing` if it's standing still or being knocked back,
+    /// since being knocked backwards shouldn't turn it around
+    pub fn facing(&self, facing: Facing) -> Facing {
+        if self.is_stunned() {
+            return facing;
+        }
+        match self.velocity.x.partial_cmp(&0.0) {
+            Some(Ordering::Greater) => Facing::Right,
+            Some(Ordering::Less) => Facing::Left,
+            _ => facing,
+        }
+    }
+}
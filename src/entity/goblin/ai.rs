@@ -0,0 +1,314 @@
+use std::time::Duration;
+
+use rand::Rng;
+
+use crate::{entity::Facing, world::space::GamePos};
+
+/// Walking pace while wandering, relative to chasing
+const PATROL_PACE: f32 = 0.5;
+
+/// Walking pace while running away, relative to chasing
+const FLEE_PACE: f32 = 1.2;
+
+/// A patrol ends once the goblin is this close to where it was heading, in units
+const PATROL_ARRIVED: f32 = 1.0;
+
+/// Tuning for how a goblin behaves
+#[derive(Clone, Copy, Debug)]
+pub struct AiParams {
+    /// Notices the player within this many units, and loses interest at twice that
+    pub sight: f32,
+    /// Stops walking up to the player this many units from their center
+    pub approach: f32,
+    /// Pause between deciding to attack and swinging, which gives the player a chance to react
+    pub wind_up: Duration,
+    /// Pause after swinging or being hit before doing anything else
+    pub recover: Duration,
+    /// Runs away when hit and left with this fraction of its health or less
+    pub flee_below: f32,
+    pub flee_time: Duration,
+    /// Wanders up to this many units either way while patrolling
+    pub patrol_range: f32,
+    /// Stands around for up to this long between patrols
+    pub idle_time: Duration,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AiState {
+    Idle { left: Duration },
+    Patrol { target_x: f32 },
+    Chase,
+    WindUp { left: Duration },
+    Attack,
+    Recover { left: Duration },
+    Flee { left: Duration },
+}
+
+/// What a goblin knows about the player when deciding what to do
+#[derive(Clone, Copy, Debug)]
+pub struct Target {
+    pub center: GamePos,
+    /// Whether the player is inside the goblin's reach
+    pub in_reach: bool,
+}
+
+/// What a goblin decided to do
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Intent {
+    /// Direction and pace to walk in, from -1 to 1 for chasing speed
+    pub walk: f32,
+    pub face: Option<Facing>,
+    /// Start swinging
+    pub attack: bool,
+    /// Whether the goblin keeps its distance from the rest of the crowd while doing it
+    pub spread: bool,
+}
+
+/// Decides what a goblin does from one step to the next
+pub struct Ai {
+    pub params: AiParams,
+    state: AiState,
+}
+
+impl Ai {
+    pub fn new(params: AiParams) -> Ai {
+        Ai {
+            params,
+            state: AiState::Idle { left: Duration::ZERO },
+        }
+    }
+    pub fn state(&self) -> AiState {
+        self.state
+    }
+    /// Moves to the next state for the coming `dt`, given where the goblin's `center` is,
+    /// what it knows of the player, and whether its attack animation is still playing
+    ///
+    /// Any chance involved comes from `rng`, so a run replays the same from the same seed
+    pub fn think<R: Rng>(
+        &mut self,
+        dt: Duration,
+        center: GamePos,
+        target: &Target,
+        attacking: bool,
+        rng: &mut R,
+    ) -> Intent {
+        let params = self.params;
+        let to_target = target.center.x - center.x;
+        let distance = to_target.abs();
+        let sees_target = distance < params.sight;
+
+        let previous = self.state;
+        self.state = match previous {
+            AiState::Idle { .. } if sees_target => AiState::Chase,
+            AiState::Idle { left } if left > dt => AiState::Idle { left: left - dt },
+            AiState::Idle { .. } => AiState::Patrol {
+                target_x: center.x + rng.gen_range(-params.patrol_range..=params.patrol_range),
+            },
+            AiState::Patrol { .. } if sees_target => AiState::Chase,
+            AiState::Patrol { target_x } if (target_x - center.x).abs() < PATROL_ARRIVED => {
+                AiState::Idle {
+                    left: params.idle_time.mul_f32(rng.gen()),
+                }
+            }
+            AiState::Patrol { target_x } => AiState::Patrol { target_x },
+            AiState::Chase if target.in_reach => AiState::WindUp {
+                left: params.wind_up,
+            },
+            AiState::Chase if distance > params.sight * 2.0 => AiState::Idle {
+                left: params.idle_time,
+            },
+            AiState::Chase => AiState::Chase,
+            AiState::WindUp { left } if left > dt => AiState::WindUp { left: left - dt },
+            AiState::WindUp { .. } => AiState::Attack,
+            AiState::Attack if attacking => AiState::Attack,
+            AiState::Attack => AiState::Recover {
+                left: params.recover,
+            },
+            AiState::Recover { left } if left > dt => AiState::Recover { left: left - dt },
+            AiState::Recover { .. } => AiState::Chase,
+            AiState::Flee { left } if left > dt => AiState::Flee { left: left - dt },
+            AiState::Flee { .. } => AiState::Chase,
+        };
+
+        let towards = to_target.signum();
+        match self.state {
+            AiState::Patrol { target_x } => Intent {
+                walk: (target_x - center.x).signum() * PATROL_PACE,
+                spread: true,
+                ..Intent::default()
+            },
+            AiState::Chase => Intent {
+                walk: if distance > params.approach { towards } else { 0.0 },
+                spread: true,
+                ..Intent::default()
+            },
+            AiState::WindUp { .. } => Intent {
+                face: Some(if towards < 0.0 { Facing::Left } else { Facing::Right }),
+                ..Intent::default()
+            },
+            AiState::Attack => Intent {
+                attack: previous != AiState::Attack,
+                ..Intent::default()
+            },
+            AiState::Flee { .. } => Intent {
+                walk: -towards * FLEE_PACE,
+                spread: true,
+                ..Intent::default()
+            },
+            AiState::Idle { .. } => Intent {
+                spread: true,
+                ..Intent::default()
+            },
+            AiState::Recover { .. } => Intent::default(),
+        }
+    }
+    /// Getting hit interrupts whatever the goblin was doing,
+    /// and sends it running if `health_left` (as a fraction) is low enough
+    pub fn hit(&mut self, health_left: f32) {
+        self.state = if health_left <= self.params.flee_below {
+            AiState::Flee {
+                left: self.params.flee_time,
+            }
+        } else {
+            AiState::Recover {
+                left: self.params.recover,
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    const STEP: Duration = Duration::from_millis(100);
+
+    fn params() -> AiParams {
+        AiParams {
+            sight: 50.0,
+            approach: 2.0,
+            wind_up: Duration::from_millis(250),
+            recover: Duration::from_millis(500),
+            flee_below: 0.34,
+            flee_time: Duration::from_millis(1_000),
+            patrol_range: 20.0,
+            idle_time: Duration::from_millis(400),
+        }
+    }
+
+    fn target(x: f32, in_reach: bool) -> Target {
+        Target {
+            center: (x, 0.0).into(),
+            in_reach,
+        }
+    }
+
+    fn origin() -> GamePos {
+        (0.0, 0.0).into()
+    }
+
+    /// Thinks until the state stops being `Idle`, with the player far out of sight
+    fn start_patrolling(ai: &mut Ai, rng: &mut StdRng) {
+        for _ in 0..10 {
+            ai.think(STEP, origin(), &target(1_000.0, false), false, rng);
+            if let AiState::Patrol { .. } = ai.state() {
+                return;
+            }
+        }
+        panic!("never started patrolling, still {:?}", ai.state());
+    }
+
+    #[test]
+    fn patrols_until_the_target_is_seen() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut ai = Ai::new(params());
+        start_patrolling(&mut ai, &mut rng);
+        let target_x = match ai.state() {
+            AiState::Patrol { target_x } => target_x,
+            state => panic!("expected a patrol, got {:?}", state),
+        };
+        assert!(target_x.abs() <= params().patrol_range);
+
+        let intent = ai.think(STEP, origin(), &target(30.0, false), false, &mut rng);
+        assert_eq!(ai.state(), AiState::Chase);
+        assert_eq!(intent.walk, 1.0);
+    }
+
+    #[test]
+    fn winds_up_then_attacks_in_reach() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut ai = Ai::new(params());
+        ai.think(STEP, origin(), &target(-3.0, false), false, &mut rng);
+        assert_eq!(ai.state(), AiState::Chase);
+
+        let intent = ai.think(STEP, origin(), &target(-1.0, true), false, &mut rng);
+        assert!(matches!(ai.state(), AiState::WindUp { .. }));
+        assert_eq!(intent.face, Some(Facing::Left));
+        assert!(!intent.attack);
+
+        let mut attacks = 0;
+        for _ in 0..4 {
+            let intent = ai.think(STEP, origin(), &target(-1.0, true), true, &mut rng);
+            attacks += intent.attack as u32;
+        }
+        assert_eq!(ai.state(), AiState::Attack);
+        assert_eq!(attacks, 1);
+
+        // Once the swing finishes, the goblin recovers
+        ai.think(STEP, origin(), &target(-1.0, true), false, &mut rng);
+        assert!(matches!(ai.state(), AiState::Recover { .. }));
+    }
+
+    #[test]
+    fn flees_when_hit_below_flee_below() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut ai = Ai::new(params());
+        ai.hit(0.5);
+        assert!(matches!(ai.state(), AiState::Recover { .. }));
+
+        ai.hit(0.3);
+        assert_eq!(
+            ai.state(),
+            AiState::Flee {
+                left: params().flee_time
+            }
+        );
+        let intent = ai.think(STEP, origin(), &target(10.0, false), false, &mut rng);
+        assert!(intent.walk < 0.0);
+
+        // Then it comes back for more
+        for _ in 0..10 {
+            ai.think(STEP, origin(), &target(10.0, false), false, &mut rng);
+        }
+        assert_eq!(ai.state(), AiState::Chase);
+    }
+
+    #[test]
+    fn goes_back_to_patrolling_when_the_target_is_lost() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut ai = Ai::new(params());
+        ai.think(STEP, origin(), &target(30.0, false), false, &mut rng);
+        assert_eq!(ai.state(), AiState::Chase);
+
+        // Still chasing just out of sight, but not twice as far
+        ai.think(STEP, origin(), &target(80.0, false), false, &mut rng);
+        assert_eq!(ai.state(), AiState::Chase);
+
+        ai.think(STEP, origin(), &target(120.0, false), false, &mut rng);
+        assert!(matches!(ai.state(), AiState::Idle { .. }));
+        start_patrolling(&mut ai, &mut rng);
+    }
+
+    #[test]
+    fn same_seed_makes_the_same_choices() {
+        let patrol = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut ai = Ai::new(params());
+            start_patrolling(&mut ai, &mut rng);
+            ai.state()
+        };
+        assert_eq!(patrol(5), patrol(5));
+    }
+}
@@ -0,0 +1,193 @@
+use std::convert::TryFrom;
+
+use rand::{distributions::WeightedIndex, prelude::Distribution, Rng};
+use serde::Deserialize;
+
+use crate::{
+    entity::projectile::ProjectileKind,
+    utility::sheet::Rgb,
+    world::difficulty::Scaling,
+};
+
+/// Where goblin kinds are defined
+pub const KINDS_PATH: &str = "data/goblins.toml";
+
+/// An archetype of goblin, with its own stats and looks
+///
+/// ```toml
+/// [[goblin]]
+/// name = "brute"
+/// speed = 220.0
+/// health = 8
+/// damage = 2
+/// size = [14.0, 14.0]
+/// reach = 2.0
+/// palette = [["#00a860", "#6a7f8c"]]
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct GoblinKind {
+    pub name: String,
+    #[serde(default = "default_sprite")]
+    pub sprite: String,
+    #[serde(default = "default_animations")]
+    pub animations: String,
+    /// Colours of `sprite` to swap for others, to tell kinds that share a sheet apart
+    #[serde(default)]
+    pub palette: Vec<(Rgb, Rgb)>,
+    /// Acceleration while walking, in units per second squared
+    pub speed: f32,
+    pub health: u32,
+    /// Damage dealt by each swing that lands
+    pub damage: u32,
+    /// Size drawn in the world, in units, which the hitboxes scale with
+    pub size: (f32, f32),
+    /// How far past its sprite the goblin can reach, in units
+    pub reach: f32,
+    /// What it fires when it attacks, if it attacks from range rather than swinging
+    #[serde(default)]
+    pub projectile: Option<ProjectileKind>,
+    /// Runs away when hit and left with this fraction of its health or less
+    #[serde(default = "default_flee_below")]
+    pub flee_below: f32,
+    /// How often this kind spawns compared to the others, when a wave doesn't say which kind
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    /// How dangerous the kind is from 1 up, which makes it more common as a run gets harder
+    #[serde(default = "default_threat")]
+    pub threat: u32,
+}
+
+impl GoblinKind {
+    /// The kind with its stats scaled up by `scaling`
+    pub fn scaled(&self, scaling: &Scaling) -> GoblinKind {
+        let mut kind = self.clone();
+        kind.speed *= scaling.speed;
+        kind.health = scale(kind.health, scaling.health);
+        kind.damage = scale(kind.damage, scaling.damage);
+        if let Some(projectile) = &mut kind.projectile {
+            projectile.damage = scale(projectile.damage, scaling.damage);
+        }
+        kind
+    }
+}
+
+/// Scales a whole number stat, which never drops below 1
+fn scale(stat: u32, by: f32) -> u32 {
+    ((stat as f32 * by).round() as u32).max(1)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "GoblinKindsFile")]
+pub struct GoblinKinds {
+    pub kinds: Vec<GoblinKind>,
+}
+
+/// The goblins file as written, before it's checked
+#[derive(Deserialize)]
+struct GoblinKindsFile {
+    #[serde(rename = "goblin")]
+    kinds: Vec<GoblinKind>,
+}
+
+impl TryFrom<GoblinKindsFile> for GoblinKinds {
+    type Error = String;
+
+    /// Refuses files where no kind can be picked by chance, since waves that don't name a kind would spawn nothing
+    fn try_from(file: GoblinKindsFile) -> Result<Self, Self::Error> {
+        if file.kinds.iter().all(|kind| kind.weight == 0) {
+            return Err(String::from("at least one goblin kind needs a weight above 0"));
+        }
+        Ok(GoblinKinds { kinds: file.kinds })
+    }
+}
+
+impl GoblinKinds {
+    pub fn get(&self, name: &str) -> Option<&GoblinKind> {
+        self.kinds.iter().find(|kind| kind.name == name)
+    }
+    /// Picks a kind at random by weight, each multiplied by `bias`, or None if no kind can spawn
+    pub fn pick<R: Rng>(&self, rng: &mut R, bias: impl Fn(&GoblinKind) -> f32) -> Option<&GoblinKind> {
+        let weights = self.kinds.iter().map(|kind| kind.weight as f32 * bias(kind));
+        let weights = WeightedIndex::new(weights).ok()?;
+        self.kinds.get(weights.sample(rng))
+    }
+}
+
+fn default_sprite() -> String {
+    String::from("img/goblin.png")
+}
+
+fn default_animations() -> String {
+    String::from("img/goblin.toml")
+}
+
+fn default_flee_below() -> f32 {
+    0.34
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+fn default_threat() -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn kinds(weights: &[u32]) -> Result<GoblinKinds, toml::de::Error> {
+        let text: String = weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| {
+                format!(
+                    "[[goblin]]\nname = \"{}\"\nspeed = 1.0\nhealth = 1\ndamage = 1\n\
+                     size = [1.0, 1.0]\nreach = 1.0\nweight = {}\n",
+                    i, weight
+                )
+            })
+            .collect();
+        toml::from_str(&text)
+    }
+
+    #[test]
+    fn scaled_stats_never_drop_below_one() {
+        let kind = &kinds(&[1]).unwrap().kinds[0];
+        let weaker = kind.scaled(&Scaling {
+            health: 0.1,
+            damage: 0.0,
+            speed: 0.5,
+        });
+        assert_eq!(weaker.health, 1);
+        assert_eq!(weaker.damage, 1);
+        assert_eq!(weaker.speed, 0.5);
+
+        let stronger = kind.scaled(&Scaling {
+            health: 2.6,
+            damage: 1.4,
+            speed: 1.0,
+        });
+        assert_eq!(stronger.health, 3);
+        assert_eq!(stronger.damage, 1);
+    }
+
+    #[test]
+    fn kinds_that_can_never_be_picked_are_refused() {
+        assert!(kinds(&[0, 0]).is_err());
+        assert!(kinds(&[]).is_err());
+        assert!(kinds(&[0, 1]).is_ok());
+    }
+
+    #[test]
+    fn pick_skips_kinds_without_weight() {
+        let kinds = kinds(&[0, 1, 0]).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..10 {
+            assert_eq!(kinds.pick(&mut rng, |_| 1.0).unwrap().name, "1");
+        }
+    }
+}
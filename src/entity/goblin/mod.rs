@@ -0,0 +1,224 @@
+use std::{ops::Range, time::Duration};
+
+use self::{
+    ai::{Ai, AiParams, AiState, Target},
+    kind::GoblinKind,
+};
+
+use rand::Rng;
+
+use speedy2d::{color::Color, Graphics2D};
+
+use crate::{
+    error::GameError,
+    screen::camera::Camera,
+    utility::{
+        animation::Animation,
+        assets,
+        animator::{AnimState, Animator},
+    },
+    world::{
+        collision::{EntityId, GameRect},
+        space::GamePos,
+        World,
+    },
+};
+
+pub mod ai;
+pub mod kind;
+
+use super::{
+    body::Body,
+    projectile::{Projectile, ProjectileKind, Team},
+    reaction::HitReaction,
+    steering::Separation,
+    Entity, Facing,
+};
+
+// Consts
+
+/// Sent flying, but only briefly protected so quick attacks still land
+const HIT_REACTION: HitReaction = HitReaction {
+    knockback: 180.0,
+    stun: Duration::from_millis(400),
+    invulnerable: Duration::from_millis(200),
+    flash: Color::from_rgba(1.0, 0.3, 0.3, 1.0),
+};
+
+/// Range each goblin picks how close it walks up to the player from, in units between their centers,
+/// so a crowd doesn't all stop in the same spot
+const APPROACH: Range<f32> = 1.0..6.0;
+
+/// Range each goblin picks its wind-up from, so a crowd doesn't swing in unison
+const WIND_UP_MS: Range<u64> = 150..350;
+
+const AI: AiParams = AiParams {
+    sight: 60.0,
+    approach: APPROACH.start,
+    wind_up: Duration::from_millis(WIND_UP_MS.start),
+    recover: Duration::from_millis(500),
+    flee_below: 0.34,
+    flee_time: Duration::from_millis(1_500),
+    patrol_range: 20.0,
+    idle_time: Duration::from_millis(2_000),
+};
+
+/// Goblins spread out into a loose crowd rather than stacking up on one point
+pub const SEPARATION: Separation = Separation {
+    radius: 8.0,
+    max_neighbours: 4,
+    strength: 1.5,
+};
+
+pub struct Goblin {
+    id: EntityId,
+    kind: String,
+    pub body: Body,
+    anim: Animator,
+    ai: Ai,
+    damage: u32,
+    reach: f32,
+    projectile: Option<ProjectileKind>,
+}
+
+impl Entity for Goblin {
+    fn update(&mut self, dt: Duration, world: &mut World) {
+        self.body.update(dt, world);
+        let params = &mut self.anim.params;
+        params.facing = self.body.facing(params.facing);
+        params.moving = self.body.velocity.x.abs() >= 0.6;
+        self.anim.update(&world.clock);
+        self.anim.add_colliders(self.id, self.body.bounds(), world);
+    }
+    fn draw(&self, graphics: &mut Graphics2D, camera: &Camera, alpha: f32) {
+        self.body.draw(&self.anim, graphics, camera, alpha);
+    }
+    fn render_pos(&self, alpha: f32) -> GamePos {
+        self.body.render_pos(alpha)
+    }
+}
+
+impl Goblin {
+    /// Loads how goblins of `kind` look, which every goblin of that kind can share
+    pub fn load_animation(graphics: &mut Graphics2D, kind: &GoblinKind) -> Result<Animation, GameError> {
+        let set = assets::load_animation_set(&kind.animations)?;
+        Ok(Animation::load_swapped(graphics, &kind.sprite, &kind.palette, set)?)
+    }
+    /// Makes a goblin of `kind` that thinks with `ai`, from `Goblin::roll_ai`,
+    /// and looks like `anim`, from `Goblin::load_animation`
+    pub fn new(
+        id: EntityId,
+        kind: &GoblinKind,
+        ai: AiParams,
+        anim: Animation,
+    ) -> Result<Goblin, GameError> {
+        let anim = Animator::new(anim, Goblin::states())?;
+        Ok(Goblin {
+            id,
+            kind: kind.name.clone(),
+            body: Body::new(kind.size, kind.speed, kind.health, HIT_REACTION),
+            anim,
+            ai: Ai::new(ai),
+            damage: kind.damage,
+            reach: kind.reach,
+            projectile: kind.projectile,
+        })
+    }
+    /// Picks how a goblin of `kind` behaves, which varies a little from goblin to goblin
+    pub fn roll_ai<R: Rng>(kind: &GoblinKind, rng: &mut R) -> AiParams {
+        AiParams {
+            approach: rng.gen_range(APPROACH),
+            wind_up: Duration::from_millis(rng.gen_range(WIND_UP_MS)),
+            flee_below: kind.flee_below,
+            ..AI
+        }
+    }
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+    /// Name of the goblin's kind
+    pub fn kind(&self) -> &str {
+        &self.kind
+    }
+    /// Damage the goblin's hitboxes deal this step, which is none for goblins that fire projectiles instead
+    pub fn strike_damage(&self) -> Option<u32> {
+        if self.projectile.is_none() && self.body.strikes(&self.anim) {
+            Some(self.damage)
+        } else {
+            None
+        }
+    }
+    /// Fires the goblin's projectile the way it's facing, if it has one and attacks this step
+    pub fn shoot(&self, world: &mut World) -> Option<Projectile> {
+        let kind = self.projectile?;
+        if !self.body.strikes(&self.anim) {
+            return None;
+        }
+        let direction = match self.anim.params.facing {
+            Facing::Right => (1.0, 0.0),
+            Facing::Left => (-1.0, 0.0),
+        };
+        Some(Projectile::new(
+            world.next_id(),
+            Team::Goblins,
+            kind,
+            self.body.bounds().center(),
+            direction.into(),
+        ))
+    }
+    /// Takes a hit like any body, and lets the AI know how hurt it is
+    pub fn take_hit(&mut self, damage: u32, from: GamePos) {
+        if self.body.take_hit(damage, from) {
+            let health = &self.body.health;
+            self.ai.hit(health.current() as f32 / health.max() as f32);
+        }
+    }
+    pub fn ai_state(&self) -> AiState {
+        self.ai.state()
+    }
+    /// Lets the AI decide what to do for the next `dt`, and starts doing it
+    ///
+    /// `push` keeps the goblin away from the rest of the crowd while it walks around
+    pub fn think<R: Rng>(&mut self, dt: Duration, target: &Target, push: GamePos, rng: &mut R) {
+        if self.body.is_stunned() {
+            return;
+        }
+        let center = self.body.bounds().center();
+        let intent = self.ai.think(dt, center, target, self.anim.params.attacking, rng);
+
+        if let Some(facing) = intent.face {
+            self.anim.params.facing = facing;
+        }
+        if intent.attack {
+            self.body.velocity.x = 0.0;
+            self.anim.params.attacking = true;
+        }
+        let spread = if intent.spread { push.x } else { 0.0 };
+        self.body.walk((intent.walk + spread, 0.0).into());
+    }
+    /// Once started, an attack always plays out in full
+    fn states() -> Vec<AnimState> {
+        vec![
+            AnimState::new(Some("attack"), 2, |p| p.attacking)
+                .uninterruptible()
+                .on_complete(|p| p.attacking = false),
+            AnimState::new(Some("move"), 1, |p| p.moving),
+            AnimState::new(Some("idle"), 0, |_| true),
+        ]
+    }
+    /// Area in front of the goblin that it attacks anything in
+    pub fn reach(&self) -> GameRect {
+        let bounds = self.body.bounds();
+        let center = bounds.center();
+        match self.anim.params.facing {
+            Facing::Right => GameRect {
+                min: (center.x, bounds.min.y).into(),
+                max: (bounds.max.x + self.reach, bounds.max.y).into(),
+            },
+            Facing::Left => GameRect {
+                min: (bounds.min.x - self.reach, bounds.min.y).into(),
+                max: (center.x, bounds.max.y).into(),
+            },
+        }
+    }
+}
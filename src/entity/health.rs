@@ -0,0 +1,25 @@
+/// Hit points, and the entity is dead once they run out
+#[derive(Clone, Copy, Debug)]
+pub struct Health {
+    current: u32,
+    max: u32,
+}
+
+impl Health {
+    pub fn new(max: u32) -> Health {
+        Health { current: max, max }
+    }
+    /// Takes `amount` off, stopping at zero
+    pub fn damage(&mut self, amount: u32) {
+        self.current = self.current.saturating_sub(amount);
+    }
+    pub fn is_dead(&self) -> bool {
+        self.current == 0
+    }
+    pub fn current(&self) -> u32 {
+        self.current
+    }
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+}
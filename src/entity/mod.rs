@@ -1,17 +1,38 @@
+use std::time::Duration;
+
 use speedy2d::Graphics2D;
 
-use crate::{screen::camera::Camera, utility::animation::AnimationSelectError, world::space::GamePos};
+use crate::{
+    screen::camera::Camera,
+    world::{space::GamePos, World},
+};
 
+pub mod body;
 pub mod goblin;
+pub mod health;
 pub mod player;
+pub mod projectile;
+pub mod reaction;
+pub mod steering;
 pub mod tile;
 
+/// Which way an entity is looking
+///
+/// Sprites are drawn facing right, and flipped when facing left
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Facing {
+    Left,
+    Right,
+}
+
 pub trait Entity {
-    fn draw(&mut self, graphics: &mut Graphics2D, camera: &Camera);
-    fn moove(&mut self, change_pos: (f32, f32));
-    fn set_anim(&mut self, anim_name: &str) -> Result<(), AnimationSelectError>;
-    fn intercept_anim(&mut self, anim_name: &str) -> Result<(), AnimationSelectError>;
-    fn remove_anim(&mut self);
-    fn accelerate(&mut self, vector: GamePos);
-    fn get_pos(&self) -> GamePos;
+    /// Advances the entity by `dt` of game time
+    fn update(&mut self, dt: Duration, world: &mut World);
+    /// Draws the entity without changing it
+    ///
+    /// `alpha` is how far the frame falls between the last two updates, from 0 to 1,
+    /// and the entity is drawn that far along its movement
+    fn draw(&self, graphics: &mut Graphics2D, camera: &Camera, alpha: f32);
+    /// Where the entity is drawn, `alpha` of the way from its previous position to its current one
+    fn render_pos(&self, alpha: f32) -> GamePos;
 }
\ No newline at end of file
@@ -1,143 +1,102 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::time::Duration;
 
-use speedy2d::{
-    color::Color,
-    image::{ImageDataType, ImageFileFormat, ImageHandle, ImageSmoothingMode},
-    shape::Rectangle,
-    Graphics2D,
-};
+use speedy2d::{color::Color, Graphics2D};
 
 use crate::{
-    screen::{
-        camera::Camera,
-        game::{self, DRAG},
+    error::GameError,
+    screen::camera::Camera,
+    utility::{
+        animation::Animation,
+        assets,
+        animator::{AnimState, Animator},
     },
-    utility::animation::{Animation, AnimationSelectError},
-    world::space::GamePos,
+    world::{collision::EntityId, space::GamePos, World},
 };
 
-use super::Entity;
+use super::{body::Body, reaction::HitReaction, Entity};
 
 // Consts
 
-const SPEED: f32 = 0.2;
+/// Acceleration while walking, in units per second squared
+const SPEED: f32 = 720.0;
 
-#[derive(Clone, Copy)]
-enum Direction {
-    Left,
-    Right,
-}
+const MAX_HEALTH: u32 = 10;
+
+/// Damage dealt by each attack that lands
+const ATTACK_DAMAGE: u32 = 1;
+
+/// Knocked back a little, and invulnerable long enough to get away from a crowd
+const HIT_REACTION: HitReaction = HitReaction {
+    knockback: 120.0,
+    stun: Duration::from_millis(250),
+    invulnerable: Duration::from_millis(1_000),
+    flash: Color::from_rgba(1.0, 1.0, 1.0, 0.3),
+};
+
+/// Upward speed at the start of a jump, in units per second
+const JUMP_SPEED: f32 = 276.0;
 
 pub struct Player {
-    pub pos: GamePos,
-    anim: Animation,
-    game_size: (f32, f32),
-    direction: Direction,
-    pub velocity: GamePos,
+    id: EntityId,
+    pub body: Body,
+    anim: Animator,
 }
 
 impl Entity for Player {
-    fn draw(&mut self, graphics: &mut speedy2d::Graphics2D, camera: &Camera) {
-        self.update();
-        self.anim.draw(
-            graphics,
-            Rectangle::from_tuples(
-                camera.game_to_pix(self.pos),
-                camera.game_to_pix(
-                    (self.pos.x + self.game_size.0, self.pos.y + self.game_size.1).into(),
-                ),
-            ),
-            Color::WHITE,
-        );
-    }
-    fn moove(&mut self, change_pos: (f32, f32)) {
-        self.velocity = (change_pos.0, change_pos.1).into();
-    }
-    fn set_anim(&mut self, anim_name: &str) -> Result<(), AnimationSelectError> {
-        let anim_name = match anim_name {
-            "move" => match self.direction {
-                Direction::Left => "move left",
-                Direction::Right => "move right",
-            },
-            "attack" => match self.direction {
-                Direction::Left => "attack left",
-                Direction::Right => "attack right",
-            },
-            _ => return Err(AnimationSelectError::NotFound),
-        };
-        self.anim.select(anim_name)
-    }
-    fn intercept_anim(&mut self, anim_name: &str) -> Result<(), AnimationSelectError> {
-        let anim_name = match anim_name {
-            "move" => match self.direction {
-                Direction::Left => "move left",
-                Direction::Right => "move right",
-            },
-            "attack" => match self.direction {
-                Direction::Left => "attack left",
-                Direction::Right => "attack right",
-            },
-            _ => return Err(AnimationSelectError::NotFound),
-        };
-        self.anim.intercept(anim_name)
-    }
-    fn remove_anim(&mut self) {
-        self.anim.deselect();
+    fn update(&mut self, dt: Duration, world: &mut World) {
+        self.body.update(dt, world);
+        let params = &mut self.anim.params;
+        params.facing = self.body.facing(params.facing);
+        self.anim.update(&world.clock);
+        self.anim.add_colliders(self.id, self.body.bounds(), world);
     }
-    fn accelerate(&mut self, vector: GamePos) {
-        self.velocity += vector * SPEED;
+    fn draw(&self, graphics: &mut Graphics2D, camera: &Camera, alpha: f32) {
+        self.body.draw(&self.anim, graphics, camera, alpha);
     }
-    fn get_pos(&self) -> GamePos {
-        self.pos
+    fn render_pos(&self, alpha: f32) -> GamePos {
+        self.body.render_pos(alpha)
     }
 }
 
 impl Player {
-    pub fn new(graphics: &mut Graphics2D) -> Player {
-        let src = graphics
-            .create_image_from_file_path(
-                Some(ImageFileFormat::PNG),
-                ImageSmoothingMode::NearestNeighbor,
-                ".\\assets\\img\\knight.png",
-            )
-            .unwrap();
-
-        let mut frames: HashMap<&'static str, (bool, Vec<(u16, u16)>)> = HashMap::new();
-
-        frames.insert(
-            "attack right",
-            (true, vec![(0, 1), (1, 1), (2, 1), (3, 1), (4, 1)]),
-        );
-        frames.insert(
-            "attack left",
-            (true, vec![(0, 2), (1, 2), (2, 2), (3, 2), (4, 2)]),
-        );
-        frames.insert("move left", (true, vec![(3, 0), (4, 0)]));
-        frames.insert("move right", (true, vec![(1, 0), (2, 0)]));
-
-        let anim = Animation::new(src, (8, 10), frames, (0, 0), 100);
-        Player {
-            pos: (0.0, 0.0).into(),
+    pub fn new(graphics: &mut Graphics2D, id: EntityId) -> Result<Player, GameError> {
+        let set = assets::load_animation_set("img/knight.toml")?;
+        let anim = Animator::new(Animation::load(graphics, "img/knight.png", set)?, Player::states())?;
+        Ok(Player {
+            id,
+            body: Body::new((8.0, 10.0), SPEED, MAX_HEALTH, HIT_REACTION),
             anim,
-            game_size: (8.0, 10.0),
-            direction: Direction::Right,
-            velocity: (0.0, 0.0).into(),
+        })
+    }
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+    /// Damage the player's hitboxes deal this step
+    pub fn strike_damage(&self) -> Option<u32> {
+        if self.body.strikes(&self.anim) {
+            Some(ATTACK_DAMAGE)
+        } else {
+            None
         }
     }
-    fn update(&mut self) {
-        self.velocity.y += game::GRAVITY;
-        self.velocity *= 1.0 - DRAG;
-        self.pos += self.velocity;
-        if self.pos.y > 0.0 {
-            self.pos.y = 0.0;
-            self.velocity.y = 0.0;
+    /// Anything pressed plays the move clip, and attacking takes priority over it
+    fn states() -> Vec<AnimState> {
+        vec![
+            AnimState::new(Some("attack"), 2, |p| p.attacking),
+            AnimState::new(Some("move"), 1, |p| p.moving),
+            AnimState::new(None, 0, |_| true),
+        ]
+    }
+    /// Leaps upwards, if standing on the ground
+    pub fn jump(&mut self) {
+        if self.body.on_ground() {
+            self.body.velocity.y = -JUMP_SPEED;
         }
-
-        self.direction = match self.velocity.x.partial_cmp(&0.0) {
-            Some(Ordering::Equal) => self.direction,
-            Some(Ordering::Greater) => Direction::Right,
-            Some(Ordering::Less) => Direction::Left,
-            None => self.direction,
-        };
+    }
+    pub fn set_moving(&mut self, moving: bool) {
+        self.anim.params.moving = moving;
+    }
+    pub fn set_attacking(&mut self, attacking: bool) {
+        self.anim.params.attacking = attacking;
     }
 }
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+use speedy2d::{shape::Rectangle, Graphics2D};
+
+use crate::{
+    screen::{camera::Camera, game},
+    utility::sheet::Rgb,
+    world::{
+        collision::{ColliderKind, EntityId, GameRect},
+        space::GamePos,
+        World,
+    },
+};
+
+use super::Entity;
+
+/// Which side fired a projectile, which is the side it can't hurt
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Team {
+    Player,
+    Goblins,
+}
+
+/// How a kind of projectile flies and what it does when it lands
+///
+/// ```toml
+/// speed = 200.0
+/// gravity = 0.1
+/// lifetime_ms = 1500
+/// pierce = 0
+/// damage = 1
+/// size = [2.0, 0.5]
+/// color = "#d8c8a0"
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct ProjectileKind {
+    /// Speed it leaves at, in units per second
+    pub speed: f32,
+    /// Fraction of the world's gravity it falls with, so 0 flies straight
+    #[serde(default)]
+    pub gravity: f32,
+    /// Disappears after this long even if it hit nothing
+    pub lifetime_ms: u64,
+    /// How many entities it passes through before the next one stops it
+    #[serde(default)]
+    pub pierce: u32,
+    pub damage: u32,
+    pub size: (f32, f32),
+    pub color: Rgb,
+}
+
+/// Arrows, thrown rocks, spells, and anything else that flies until it hits something
+pub struct Projectile {
+    id: EntityId,
+    pub team: Team,
+    kind: ProjectileKind,
+    pos: GamePos,
+    /// Position before the last update, for smoothing between updates when drawing
+    prev_pos: GamePos,
+    velocity: GamePos,
+    age: Duration,
+    /// Entities it already hit, so it doesn't hit the one it's passing through again every step
+    hit: Vec<EntityId>,
+    spent: bool,
+}
+
+impl Entity for Projectile {
+    fn update(&mut self, dt: Duration, world: &mut World) {
+        self.age += dt;
+        let dt = dt.as_secs_f32();
+        self.prev_pos = self.pos;
+
+        self.velocity.y += game::GRAVITY * self.kind.gravity * dt;
+        let sweep = world.terrain.sweep(self.bounds(), self.velocity * dt);
+        self.pos += sweep.moved;
+
+        if self.age >= Duration::from_millis(self.kind.lifetime_ms) || sweep.hit_x || sweep.hit_y {
+            self.spent = true;
+        }
+        if !self.spent {
+            world.add_collider(self.id, ColliderKind::Hitbox, self.bounds());
+        }
+    }
+    fn draw(&self, graphics: &mut Graphics2D, camera: &Camera, alpha: f32) {
+        let pos = self.render_pos(alpha);
+        graphics.draw_rectangle(
+            Rectangle::from_tuples(
+                camera.game_to_pix(pos),
+                camera.game_to_pix((pos.x + self.kind.size.0, pos.y + self.kind.size.1).into()),
+            ),
+            self.kind.color.to_color(),
+        );
+    }
+    fn render_pos(&self, alpha: f32) -> GamePos {
+        self.prev_pos.lerp(self.pos, alpha)
+    }
+}
+
+impl Projectile {
+    /// Fires a projectile for `team` centered on `from`, flying in `direction`
+    pub fn new(
+        id: EntityId,
+        team: Team,
+        kind: ProjectileKind,
+        from: GamePos,
+        direction: GamePos,
+    ) -> Projectile {
+        let pos = from - GamePos::from(kind.size) / 2.0;
+        Projectile {
+            id,
+            team,
+            kind,
+            pos,
+            prev_pos: pos,
+            velocity: direction * kind.speed,
+            age: Duration::ZERO,
+            hit: Vec::new(),
+            spent: false,
+        }
+    }
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+    pub fn bounds(&self) -> GameRect {
+        GameRect::new(self.pos, self.kind.size)
+    }
+    /// Damage dealt by landing on `target`, on `target_team`, or None if it's on the same team,
+    /// already hit it or has stopped
+    ///
+    /// Counts as a hit, so the projectile stops once it has gone through as many entities as it pierces.
+    /// Its own team doesn't count, so it flies straight through them
+    pub fn strike(&mut self, target: EntityId, target_team: Team) -> Option<u32> {
+        if self.spent || target_team == self.team || self.hit.contains(&target) {
+            return None;
+        }
+        self.hit.push(target);
+        if self.hit.len() as u32 > self.kind.pierce {
+            self.spent = true;
+        }
+        Some(self.kind.damage)
+    }
+    /// Whether it has landed, hit the ground or timed out, and can be cleared away
+    pub fn is_spent(&self) -> bool {
+        self.spent
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bolt(pierce: u32) -> Projectile {
+        let kind = ProjectileKind {
+            speed: 100.0,
+            gravity: 0.0,
+            lifetime_ms: 1_000,
+            pierce,
+            damage: 2,
+            size: (1.0, 1.0),
+            color: Rgb([255, 255, 255]),
+        };
+        Projectile::new(EntityId(0), Team::Goblins, kind, (0.0, 0.0).into(), (1.0, 0.0).into())
+    }
+
+    #[test]
+    fn strikes_each_target_once() {
+        let mut bolt = bolt(2);
+        assert_eq!(bolt.strike(EntityId(1), Team::Player), Some(2));
+        assert_eq!(bolt.strike(EntityId(1), Team::Player), None);
+        assert!(!bolt.is_spent());
+    }
+
+    #[test]
+    fn spent_after_piercing_its_limit() {
+        let mut bolt = bolt(1);
+        assert_eq!(bolt.strike(EntityId(1), Team::Player), Some(2));
+        assert!(!bolt.is_spent());
+        assert_eq!(bolt.strike(EntityId(2), Team::Player), Some(2));
+        assert!(bolt.is_spent());
+        assert_eq!(bolt.strike(EntityId(3), Team::Player), None);
+    }
+
+    #[test]
+    fn without_pierce_the_first_hit_stops_it() {
+        let mut bolt = bolt(0);
+        assert_eq!(bolt.strike(EntityId(1), Team::Player), Some(2));
+        assert!(bolt.is_spent());
+    }
+    #[test]
+    fn pierces_the_other_team_and_passes_its_own() {
+        let mut bolt = bolt(1);
+        assert_eq!(bolt.strike(EntityId(1), Team::Goblins), None);
+        assert_eq!(bolt.strike(EntityId(2), Team::Player), Some(2));
+        assert_eq!(bolt.strike(EntityId(3), Team::Goblins), None);
+        assert!(!bolt.is_spent());
+        assert_eq!(bolt.strike(EntityId(4), Team::Player), Some(2));
+        assert!(bolt.is_spent());
+    }
+}
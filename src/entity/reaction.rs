@@ -0,0 +1,66 @@
+use std::time::Duration;
+
+use speedy2d::color::Color;
+
+/// Flashes alternate between tinted and untinted this often while invulnerable
+const FLASH_MS: u128 = 80;
+
+/// How an entity reacts to being hit
+#[derive(Clone, Copy, Debug)]
+pub struct HitReaction {
+    /// Speed the hit knocks the entity away at, in units per second
+    pub knockback: f32,
+    /// How long the entity can't act after being hit
+    pub stun: Duration,
+    /// How long further hits are ignored after being hit
+    pub invulnerable: Duration,
+    /// Tint the entity flashes while invulnerable
+    pub flash: Color,
+}
+
+/// An entity's recovery from its last hit
+pub struct Recovery {
+    reaction: HitReaction,
+    stun_left: Duration,
+    invulnerable_left: Duration,
+}
+
+impl Recovery {
+    pub fn new(reaction: HitReaction) -> Recovery {
+        Recovery {
+            reaction,
+            stun_left: Duration::ZERO,
+            invulnerable_left: Duration::ZERO,
+        }
+    }
+    /// Starts recovering from a hit, returning false if the entity is still invulnerable from the last one
+    pub fn hit(&mut self) -> bool {
+        if self.is_invulnerable() {
+            return false;
+        }
+        self.stun_left = self.reaction.stun;
+        self.invulnerable_left = self.reaction.invulnerable;
+        true
+    }
+    pub fn update(&mut self, dt: Duration) {
+        self.stun_left = self.stun_left.saturating_sub(dt);
+        self.invulnerable_left = self.invulnerable_left.saturating_sub(dt);
+    }
+    pub fn is_stunned(&self) -> bool {
+        !self.stun_left.is_zero()
+    }
+    pub fn is_invulnerable(&self) -> bool {
+        !self.invulnerable_left.is_zero()
+    }
+    pub fn knockback(&self) -> f32 {
+        self.reaction.knockback
+    }
+    /// Color to draw the entity with, flashing while it's invulnerable
+    pub fn tint(&self) -> Color {
+        if self.is_invulnerable() && (self.invulnerable_left.as_millis() / FLASH_MS) % 2 == 0 {
+            self.reaction.flash
+        } else {
+            Color::WHITE
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::world::space::GamePos;
+
+/// Tuning for how a crowd keeps its members apart
+#[derive(Clone, Copy, Debug)]
+pub struct Separation {
+    /// Neighbours further away than this, in units, are ignored
+    pub radius: f32,
+    /// Only this many of the closest neighbours push each member away
+    pub max_neighbours: usize,
+    /// How hard the closest possible neighbour pushes, relative to walking
+    pub strength: f32,
+}
+
+/// How hard, and which way, each member of a crowd at `positions` should walk to get away from its neighbours
+///
+/// Each neighbour pushes harder the closer it is. Members in exactly the same spot
+/// are split up by their order, so a crowd spawned on one point still spreads out
+pub fn separation(positions: &[GamePos], params: &Separation) -> Vec<GamePos> {
+    positions
+        .iter()
+        .enumerate()
+        .map(|(i, pos)| {
+            let mut neighbours: Vec<(usize, GamePos, f32)> = positions
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .map(|(j, other)| {
+                    let offset = *pos - *other;
+                    (j, offset, offset.magnitude())
+                })
+                .filter(|(_, _, distance)| *distance < params.radius)
+                .collect();
+            neighbours.sort_by(|a, b| a.2.total_cmp(&b.2));
+            neighbours.truncate(params.max_neighbours);
+
+            let mut push: GamePos = (0.0, 0.0).into();
+            for (j, offset, distance) in neighbours {
+                let away = if distance > f32::EPSILON {
+                    offset / distance
+                } else if i < j {
+                    (-1.0, 0.0).into()
+                } else {
+                    (1.0, 0.0).into()
+                };
+                push += away * (1.0 - distance / params.radius);
+            }
+            // However crowded it gets, nobody gets shoved harder than `strength`
+            let magnitude = push.magnitude();
+            if magnitude > 1.0 {
+                push /= magnitude;
+            }
+            push * params.strength
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: Separation = Separation {
+        radius: 10.0,
+        max_neighbours: 4,
+        strength: 2.0,
+    };
+
+    fn pos(x: f32, y: f32) -> GamePos {
+        GamePos { x, y }
+    }
+
+    #[test]
+    fn overlapping_members_are_pushed_apart_evenly() {
+        let pushes = separation(&[pos(0.0, 0.0), pos(4.0, 3.0)], &PARAMS);
+        assert_eq!(pushes[0], pushes[1] * -1.0);
+        // Pushed away from each other, half as hard as the closest neighbour could
+        assert!(pushes[0].x < 0.0 && pushes[0].y < 0.0);
+        assert!((pushes[0].magnitude() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn nobody_is_pushed_from_beyond_the_radius() {
+        let pushes = separation(&[pos(0.0, 0.0), pos(10.0, 0.0), pos(0.0, -25.0)], &PARAMS);
+        for push in pushes {
+            assert_eq!(push, pos(0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn crowds_push_no_harder_than_the_strength() {
+        let crowd = [
+            pos(0.0, 0.0),
+            pos(0.5, 0.0),
+            pos(0.5, 0.1),
+            pos(0.5, -0.1),
+            pos(0.6, 0.0),
+        ];
+        for push in separation(&crowd, &PARAMS) {
+            assert!(push.magnitude() <= PARAMS.strength + 1e-5);
+        }
+        // The member on the edge has every neighbour on one side, so it's pushed as hard as allowed
+        let edge = separation(&crowd, &PARAMS)[0];
+        assert!((edge.magnitude() - PARAMS.strength).abs() < 1e-5);
+    }
+
+    #[test]
+    fn members_on_the_same_spot_are_split_up() {
+        let pushes = separation(&[pos(3.0, 3.0), pos(3.0, 3.0)], &PARAMS);
+        for push in &pushes {
+            assert!(push.x.is_finite() && push.y.is_finite());
+        }
+        assert_eq!(pushes[0], pos(-PARAMS.strength, 0.0));
+        assert_eq!(pushes[1], pos(PARAMS.strength, 0.0));
+    }
+}
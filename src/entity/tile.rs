@@ -1,21 +1,19 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::time::Duration;
 
-use speedy2d::{Graphics2D, color::Color, image::{ImageDataType, ImageFileFormat, ImageHandle, ImageSmoothingMode}, shape::Rectangle};
+use speedy2d::{Graphics2D, color::Color, shape::Rectangle};
 
 use crate::{
+    error::GameError,
     screen::camera::Camera,
-    utility::animation::{Animation, AnimationSelectError},
-    world::space::GamePos,
+    utility::{
+        animation::{Animation, Flip},
+        assets,
+    },
+    world::{space::GamePos, terrain::TILE_SIZE, World},
 };
 
 use super::Entity;
 
-#[derive(Clone, Copy)]
-enum Direction {
-    Left,
-    Right,
-}
-
 pub struct Tile {
     pos: GamePos,
     anim: Animation,
@@ -23,7 +21,8 @@ pub struct Tile {
 }
 
 impl Entity for Tile {
-    fn draw(&mut self, graphics: &mut speedy2d::Graphics2D, camera: &Camera) {
+    fn update(&mut self, _dt: Duration, _world: &mut World) {}
+    fn draw(&self, graphics: &mut Graphics2D, camera: &Camera, _alpha: f32) {
         self.anim.draw(
             graphics,
             Rectangle::from_tuples(
@@ -33,38 +32,23 @@ impl Entity for Tile {
                 ),
             ),
             Color::WHITE,
+            Flip::NONE,
         );
     }
-    fn moove(&mut self, change_pos: (f32, f32)) {
-        self.pos = (self.pos.x + change_pos.0, self.pos.y + change_pos.1).into();
-    }
-    fn set_anim(&mut self, anim_name: &str) -> Result<(), AnimationSelectError> {
-        Ok(())
-    }
-    fn intercept_anim(&mut self, anim_name: &str) -> Result<(), AnimationSelectError> {
-        Ok(())
-    }
-    fn remove_anim(&mut self) {}
-    fn accelerate(&mut self, vector: GamePos) {}
-    fn get_pos(&self) -> GamePos {
+    fn render_pos(&self, _alpha: f32) -> GamePos {
         self.pos
     }
 }
 
 impl Tile {
-    pub fn new(graphics: &mut Graphics2D, display: (u16, u16), pos: (f32, f32)) -> Tile {
-        let src = graphics
-            .create_image_from_file_path(
-                Some(ImageFileFormat::PNG),
-                ImageSmoothingMode::NearestNeighbor,
-                ".\\assets\\img\\tiles.png",
-            )
-            .unwrap();
-        let anim = Animation::new(src, (5, 5), HashMap::new(), display, 100);
-        Tile {
+    pub fn new(graphics: &mut Graphics2D, display: (u16, u16), pos: (f32, f32)) -> Result<Tile, GameError> {
+        let mut set = assets::load_animation_set("img/tiles.toml")?;
+        // Set before loading so the tile is checked against the sheet like any other frame
+        set.default = display;
+        Ok(Tile {
             pos: pos.into(),
-            anim,
-            game_size: (5.0, 5.0),
-        }
+            anim: Animation::load(graphics, "img/tiles.png", set)?,
+            game_size: (TILE_SIZE, TILE_SIZE),
+        })
     }
 }
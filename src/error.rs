@@ -0,0 +1,45 @@
+use std::fmt;
+
+use speedy2d::{error::BacktraceError, window::WindowCreationError};
+
+use crate::{utility::assets::AssetError, world::level::tiled::ImportError};
+
+/// Anything that stops the game, or one of its screens, from starting
+#[derive(Debug)]
+pub enum GameError {
+    Window(BacktraceError<WindowCreationError>),
+    Asset(AssetError),
+    /// An animator state refers to a clip its animation doesn't have
+    MissingClip(String),
+    /// A wave asks for a goblin kind that isn't defined
+    MissingGoblinKind(String),
+    /// A wave spawns goblins at a marker the level doesn't have
+    MissingSpawn(String),
+    /// A map made in Tiled couldn't be turned into a level
+    Import { path: String, error: ImportError },
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::Window(error) => write!(f, "could not open the window: {}", error),
+            GameError::Asset(error) => write!(f, "{}", error),
+            GameError::MissingClip(clip) => write!(f, "no animation has a clip called \"{}\"", clip),
+            GameError::MissingGoblinKind(kind) => write!(f, "no goblin kind is called \"{}\"", kind),
+            GameError::MissingSpawn(marker) => write!(f, "the level has no spawn marker called \"{}\"", marker),
+            GameError::Import { path, error } => write!(f, "could not import the map {}: {}", path, error),
+        }
+    }
+}
+
+impl From<AssetError> for GameError {
+    fn from(e: AssetError) -> Self {
+        GameError::Asset(e)
+    }
+}
+
+impl From<BacktraceError<WindowCreationError>> for GameError {
+    fn from(e: BacktraceError<WindowCreationError>) -> Self {
+        GameError::Window(e)
+    }
+}
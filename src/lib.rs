@@ -1,15 +1,17 @@
-use std::sync::atomic::Ordering;
 
-use screen::RESOLUTION;
 use speedy2d::{Window, dimen::Vector2, window::{WindowCreationOptions, WindowPosition, WindowSize}};
 
 mod entity;
+mod error;
 mod screen;
 mod ui;
 mod world;
 mod utility;
 
-pub fn run() {
+pub use error::GameError;
+
+/// Opens the game window, which never returns unless something goes wrong on the way
+pub fn run() -> Result<(), GameError> {
     let res = screen::get_resolution();
     let window: Window<String> = Window::new_with_user_events(
         "Goblins",
@@ -19,10 +21,32 @@ pub fn run() {
             )),
             Some(WindowPosition::Center),
         ),
-    )
-    .unwrap();
+    )?;
+
+    let title = screen::title::TitleScreen::new(Default::default())?;
+    window.run_loop(screen::RedirectHandler::new(Box::new(title)));
+}
+
+/// Reads every asset from `root` rather than searching next to the executable and working directory
+pub fn set_asset_root(root: &str) {
+    utility::asset_path::set_root(root.into());
+}
+
+/// Starts runs on the level at `path`, relative to the assets, rather than the default one
+pub fn set_start_level(path: &str) {
+    world::level::set_start_level(path.into());
+}
+
+/// Plays every run from `seed`, so a level or a run that went a certain way can be seen again
+pub fn set_seed(seed: u64) {
+    world::seed::set_seed(seed);
+}
 
-    window.run_loop(screen::RedirectHandler::new(Box::new(
-        screen::title::TitleScreen::new(),
-    )));
+/// Re-exports the tightly packed sprite sheet at `input` to `output`,
+/// surrounding every frame with `border` pixels copied from its edges
+pub fn extrude_sheet(input: &str, output: &str, frame_size: (u16, u16), border: u16) -> Result<(), String> {
+    let from = utility::animation::SheetLayout::new(frame_size);
+    utility::sheet::extrude_file(input, output, from, border)
+        .map(|_| ())
+        .map_err(|e| format!("Could not extrude {}: {}", input, e))
 }
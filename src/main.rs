@@ -1,3 +1,73 @@
+use std::env;
+
 fn main() {
-    goblins::run();
-}
\ No newline at end of file
+    let mut args: Vec<String> = env::args().skip(1).collect();
+
+    // `--assets <dir>` reads assets from <dir> instead of searching for them
+    if let Some(i) = args.iter().position(|a| a == "--assets") {
+        match args.get(i + 1) {
+            Some(root) => goblins::set_asset_root(root),
+            None => {
+                eprintln!("Usage: goblins --assets <dir>");
+                std::process::exit(1);
+            }
+        }
+        args.drain(i..=i + 1);
+    }
+
+    // `--level <file>` starts runs on <file>, which can be a level file, a Tiled map,
+    // or `generated` for a level made up from the run's seed
+    if let Some(i) = args.iter().position(|a| a == "--level") {
+        match args.get(i + 1) {
+            Some(path) => goblins::set_start_level(path),
+            None => {
+                eprintln!("Usage: goblins --level <file>");
+                std::process::exit(1);
+            }
+        }
+        args.drain(i..=i + 1);
+    }
+
+    // `--seed <number>` plays every run from <number>, instead of a new seed each time
+    if let Some(i) = args.iter().position(|a| a == "--seed") {
+        match args.get(i + 1).and_then(|seed| seed.parse().ok()) {
+            Some(seed) => goblins::set_seed(seed),
+            None => {
+                eprintln!("Usage: goblins --seed <number>");
+                std::process::exit(1);
+            }
+        }
+        args.drain(i..=i + 1);
+    }
+
+    match args.first().map(|s| &s[..]) {
+        Some("extrude") => extrude(&args[1..]),
+        _ => {
+            if let Err(e) = goblins::run() {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+/// `goblins extrude <input> <output> <frame width> <frame height> [border]`
+fn extrude(args: &[String]) {
+    if args.len() < 4 {
+        eprintln!("Usage: goblins extrude <input> <output> <frame width> <frame height> [border]");
+        std::process::exit(1);
+    }
+    let parse = |arg: &String| -> u16 {
+        arg.parse().unwrap_or_else(|_| {
+            eprintln!("Expected a number, found {}", arg);
+            std::process::exit(1);
+        })
+    };
+    let frame_size = (parse(&args[2]), parse(&args[3]));
+    let border = args.get(4).map(parse).unwrap_or(1);
+
+    if let Err(e) = goblins::extrude_sheet(&args[0], &args[1], frame_size, border) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
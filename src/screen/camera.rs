@@ -1,5 +1,3 @@
-use speedy2d::shape::Rectangle;
-
 use crate::world::space::GamePos;
 
 use super::get_resolution;
@@ -8,7 +6,6 @@ pub struct Camera {
     pub pos: GamePos,
     pub width: f32,
     pub height: f32,
-    velocity: GamePos,
 }
 impl Camera {
     pub fn new(pos: (f32, f32), width: f32, height: f32) -> Camera {
@@ -16,7 +13,6 @@ impl Camera {
             pos: pos.into(),
             width,
             height,
-            velocity: (0.0, 0.0).into(),
         }
     }
     pub fn moove(&mut self, change_pos: (f32, f32)) {
@@ -28,7 +24,15 @@ impl Camera {
             ((self.width / 2.0) + point.x - self.pos.x) / self.width,
             ((self.height / 2.0) + point.y - self.pos.y) / self.height,
         );
-        let resul = (a_pos.0 * (res.0 as f32), a_pos.1 * (res.1 as f32));
-        resul
+        (a_pos.0 * (res.0 as f32), a_pos.1 * (res.1 as f32))
+    }
+    /// The point in the game drawn at `pix`, undoing `game_to_pix`
+    pub fn pix_to_game(&self, pix: (f32, f32)) -> GamePos {
+        let res = get_resolution();
+        (
+            pix.0 / res.0 as f32 * self.width - self.width / 2.0 + self.pos.x,
+            pix.1 / res.1 as f32 * self.height - self.height / 2.0 + self.pos.y,
+        )
+            .into()
     }
 }
\ No newline at end of file
@@ -0,0 +1,448 @@
+use std::{collections::HashSet, time::Duration};
+
+use speedy2d::{
+    color::Color,
+    font::{Font, TextLayout, TextOptions},
+    shape::Rectangle,
+    window::{MouseButton, VirtualKeyCode, WindowHandler, WindowHelper},
+    Graphics2D,
+};
+
+use crate::{
+    error::GameError,
+    utility::{
+        animation::{Animation, Flip},
+        assets,
+        clock::{Clock, ClockMode},
+    },
+    world::{
+        level::{self, Level, SpawnMarker},
+        seed,
+        space::GamePos,
+        terrain::TILE_SIZE,
+    },
+};
+
+use super::{
+    camera::Camera, get_mouse_pos, get_resolution, settings::Settings, title::TitleScreen, Screen,
+};
+
+/// Pixels per unit when the editor opens, the same as in a run
+const START_SCALE: f32 = 10.0;
+
+/// How far in and out the view zooms, in pixels per unit
+const MIN_SCALE: f32 = 2.0;
+const MAX_SCALE: f32 = 40.0;
+
+/// How much each press of a zoom key zooms by
+const ZOOM_STEP: f32 = 1.25;
+
+/// How fast the arrow keys pan the view, in pixels per second
+const PAN_SPEED: f32 = 600.0;
+
+/// Size of each tile in the palette along the top of the screen, in pixels
+const SWATCH_SIZE: f32 = 40.0;
+const SWATCH_GAP: f32 = 5.0;
+
+/// How close to a spawn marker a right click has to be to remove it, in units
+const PICK_RADIUS: f32 = 5.0;
+
+/// Size markers are drawn at, in units
+const MARKER_SIZE: f32 = 4.0;
+
+/// What clicking in the level does
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tool {
+    /// Left paints the picked tile, right clears it and middle picks the tile under the cursor
+    Tiles,
+    /// Left moves the player start
+    PlayerStart,
+    /// Left adds a goblin spawn marker and right removes the nearest one
+    Spawns,
+}
+
+impl Tool {
+    fn name(self) -> &'static str {
+        match self {
+            Tool::Tiles => "Tiles",
+            Tool::PlayerStart => "Player start",
+            Tool::Spawns => "Spawns",
+        }
+    }
+}
+
+/// Builds levels by painting tiles and placing markers with the mouse
+///
+/// Edits the level runs start on, saving Tiled maps and generated levels as new level files
+pub struct EditorScreen {
+    new_screen: Option<Box<dyn Screen>>,
+    level: Level,
+    /// Where the level was loaded from
+    path: String,
+    /// Seed the level was made from, if it was generated
+    seed: u64,
+    camera: Camera,
+    /// Pixels per unit
+    scale: f32,
+    tool: Tool,
+    /// Tile painted by the tiles tool
+    tile: u16,
+    /// Every tile in the tileset, by index, loaded on the first draw
+    tiles: Option<Vec<Animation>>,
+    /// Mouse button dragged across the level, if any
+    dragging: Option<MouseButton>,
+    held_keys: HashSet<VirtualKeyCode>,
+    clock: Clock,
+    font: Font,
+    /// What happened last, like a save going through or failing
+    status: String,
+    settings: Settings,
+}
+
+impl WindowHandler<String> for EditorScreen {
+    fn on_draw(&mut self, helper: &mut WindowHelper<String>, graphics: &mut Graphics2D) {
+        graphics.clear_screen(self.level.background.to_color());
+        if self.tiles.is_none() {
+            match load_tiles(graphics) {
+                Ok(tiles) => self.tiles = Some(tiles),
+                Err(e) => {
+                    self.new_screen = Some(Box::new(super::error::ErrorScreen::new(e, self.settings)));
+                    return;
+                }
+            }
+        }
+
+        let dt = self.clock.tick();
+        self.pan(dt);
+
+        self.draw_level(graphics);
+        self.draw_markers(graphics);
+        self.draw_cursor(graphics);
+        self.draw_palette(graphics);
+        self.draw_hud(graphics);
+
+        helper.request_redraw();
+    }
+    fn on_mouse_button_down(&mut self, _helper: &mut WindowHelper<String>, button: MouseButton) {
+        let mouse = get_mouse_pos();
+        let mouse = (mouse.0 as f32, mouse.1 as f32);
+        if let Some(tile) = swatch_at(mouse) {
+            if button == MouseButton::Left {
+                self.tile = tile;
+                self.tool = Tool::Tiles;
+            }
+            return;
+        }
+        match self.tool {
+            Tool::Tiles => {
+                self.dragging = Some(button);
+                self.paint(button);
+            }
+            Tool::PlayerStart => {
+                if button == MouseButton::Left {
+                    self.level.player_start = self.cursor();
+                }
+            }
+            Tool::Spawns => match button {
+                MouseButton::Left => self.add_spawn(),
+                MouseButton::Right => self.remove_spawn(),
+                _ => (),
+            },
+        }
+    }
+    fn on_mouse_button_up(&mut self, _helper: &mut WindowHelper<String>, _button: MouseButton) {
+        self.dragging = None;
+    }
+    fn on_mouse_move(&mut self, _helper: &mut WindowHelper<String>, _position: speedy2d::dimen::Vector2<f32>) {
+        if let Some(button) = self.dragging {
+            self.paint(button);
+        }
+    }
+    fn on_key_down(
+        &mut self,
+        _helper: &mut WindowHelper<String>,
+        virtual_key_code: Option<VirtualKeyCode>,
+        _scancode: speedy2d::window::KeyScancode,
+    ) {
+        let key = match virtual_key_code {
+            Some(key) => key,
+            None => return,
+        };
+        self.held_keys.insert(key);
+        match key {
+            VirtualKeyCode::Escape => {
+                self.new_screen = Some(super::or_error(TitleScreen::new(self.settings), self.settings));
+            }
+            VirtualKeyCode::T => self.tool = Tool::Tiles,
+            VirtualKeyCode::P => self.tool = Tool::PlayerStart,
+            VirtualKeyCode::G => self.tool = Tool::Spawns,
+            VirtualKeyCode::Equals | VirtualKeyCode::NumpadAdd => self.zoom(ZOOM_STEP),
+            VirtualKeyCode::Minus | VirtualKeyCode::NumpadSubtract => self.zoom(1.0 / ZOOM_STEP),
+            VirtualKeyCode::S => self.save(),
+            VirtualKeyCode::L => self.load(),
+            _ => (),
+        }
+    }
+    fn on_key_up(
+        &mut self,
+        _helper: &mut WindowHelper<String>,
+        virtual_key_code: Option<VirtualKeyCode>,
+        _scancode: speedy2d::window::KeyScancode,
+    ) {
+        if let Some(key) = virtual_key_code {
+            self.held_keys.remove(&key);
+        }
+    }
+    fn on_resize(
+        &mut self,
+        _helper: &mut WindowHelper<String>,
+        size_pixels: speedy2d::dimen::Vector2<u32>,
+    ) {
+        self.camera.width = size_pixels.x as f32 / self.scale;
+        self.camera.height = size_pixels.y as f32 / self.scale;
+    }
+}
+
+impl Screen for EditorScreen {
+    fn change_screen(&mut self) -> Option<Box<dyn Screen>> {
+        if self.new_screen.is_some() {
+            return self.new_screen.take();
+        }
+        None
+    }
+}
+
+impl EditorScreen {
+    /// Opens the level runs start on
+    pub fn new(settings: Settings) -> Result<EditorScreen, GameError> {
+        let path = level::start_level().to_string();
+        let seed = seed::run_seed();
+        let level = level::load_seeded(&path, seed)?;
+        let res = get_resolution();
+        Ok(EditorScreen {
+            new_screen: None,
+            camera: Camera::new(
+                level.player_start.into(),
+                res.0 as f32 / START_SCALE,
+                res.1 as f32 / START_SCALE,
+            ),
+            level,
+            path,
+            seed,
+            scale: START_SCALE,
+            tool: Tool::Tiles,
+            tile: 0,
+            tiles: None,
+            dragging: None,
+            held_keys: HashSet::new(),
+            clock: Clock::new(ClockMode::Real),
+            font: assets::load_font("font/Cabal-w5j3.ttf")?,
+            status: String::new(),
+            settings,
+        })
+    }
+    /// Where in the level the mouse is pointing
+    fn cursor(&self) -> GamePos {
+        let mouse = get_mouse_pos();
+        self.camera.pix_to_game((mouse.0 as f32, mouse.1 as f32))
+    }
+    /// Uses the tiles tool on the cell under the mouse with `button`
+    fn paint(&mut self, button: MouseButton) {
+        let mouse = get_mouse_pos();
+        if self.tool != Tool::Tiles || swatch_at((mouse.0 as f32, mouse.1 as f32)).is_some() {
+            return;
+        }
+        let cell = level::cell_at(self.cursor());
+        match button {
+            MouseButton::Left => self.level.set_tile(cell, Some(self.tile)),
+            MouseButton::Right => self.level.set_tile(cell, None),
+            MouseButton::Middle => {
+                if let Some(tile) = self.level.tile(cell) {
+                    self.tile = tile;
+                }
+            }
+            _ => (),
+        }
+    }
+    /// Adds a spawn marker under the mouse, named after the first number no other marker has
+    fn add_spawn(&mut self) {
+        let name = (1..)
+            .map(|n| format!("spawn {}", n))
+            .find(|name| self.level.spawn(name).is_none())
+            .unwrap_or_default();
+        self.status = format!("Added {}", name);
+        self.level.spawns.push(SpawnMarker {
+            name,
+            pos: self.cursor().into(),
+        });
+    }
+    fn remove_spawn(&mut self) {
+        let cursor = self.cursor();
+        let distance = |spawn: &SpawnMarker| (GamePos::from(spawn.pos) - cursor).magnitude();
+        let nearest = self
+            .level
+            .spawns
+            .iter()
+            .enumerate()
+            .filter(|(_, spawn)| distance(spawn) <= PICK_RADIUS)
+            .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+            .map(|(i, _)| i);
+        if let Some(i) = nearest {
+            let spawn = self.level.spawns.remove(i);
+            self.status = format!("Removed {}", spawn.name);
+        }
+    }
+    /// Zooms by `factor` about the middle of the screen
+    fn zoom(&mut self, factor: f32) {
+        self.scale = (self.scale * factor).clamp(MIN_SCALE, MAX_SCALE);
+        let res = get_resolution();
+        self.camera.width = res.0 as f32 / self.scale;
+        self.camera.height = res.1 as f32 / self.scale;
+    }
+    /// Moves the view for as long as `dt` with whichever arrow keys are held
+    fn pan(&mut self, dt: Duration) {
+        let speed = PAN_SPEED / self.scale * dt.as_secs_f32();
+        let held = |key| self.held_keys.contains(&key);
+        let x = held(VirtualKeyCode::Right) as i32 - held(VirtualKeyCode::Left) as i32;
+        let y = held(VirtualKeyCode::Down) as i32 - held(VirtualKeyCode::Up) as i32;
+        self.camera.moove((x as f32 * speed, y as f32 * speed));
+    }
+    fn save(&mut self) {
+        let path = level::save_path(&self.path);
+        self.status = match self.level.save(&path) {
+            Ok(written) => {
+                // Once saved, the level file is what gets loaded back
+                self.path = path;
+                format!("Saved to {}", written)
+            }
+            Err(e) => format!("Could not save: {}", e),
+        };
+    }
+    /// Throws away any changes since the last save
+    fn load(&mut self) {
+        self.status = match level::load_seeded(&self.path, self.seed) {
+            Ok(level) => {
+                self.level = level;
+                format!("Loaded {}", self.path)
+            }
+            Err(e) => format!("Could not load: {}", e),
+        };
+    }
+    fn draw_level(&self, graphics: &mut Graphics2D) {
+        let tiles = match &self.tiles {
+            Some(tiles) => tiles,
+            None => return,
+        };
+        for (cell, tile) in self.level.cells() {
+            if let Some(anim) = tiles.get(tile as usize) {
+                anim.draw(graphics, self.cell_rect(cell), Color::WHITE, Flip::NONE);
+            }
+        }
+    }
+    fn draw_markers(&self, graphics: &mut Graphics2D) {
+        self.draw_marker(graphics, self.level.player_start, "start", Color::from_rgba(0.0, 0.0, 1.0, 0.6));
+        for spawn in self.level.spawns.iter() {
+            self.draw_marker(graphics, spawn.pos.into(), &spawn.name, Color::from_rgba(1.0, 0.0, 0.0, 0.6));
+        }
+    }
+    fn draw_marker(&self, graphics: &mut Graphics2D, pos: GamePos, label: &str, color: Color) {
+        let half = MARKER_SIZE / 2.0;
+        graphics.draw_rectangle(
+            Rectangle::from_tuples(
+                self.camera.game_to_pix(pos - (half, half).into()),
+                self.camera.game_to_pix(pos + (half, half).into()),
+            ),
+            color,
+        );
+        let above = pos - (half, half + 2.0).into();
+        graphics.draw_text(
+            self.camera.game_to_pix(above),
+            Color::BLACK,
+            &self.font.layout_text(label, 16.0, TextOptions::new()),
+        );
+    }
+    /// Shows what a click would do where the mouse is
+    fn draw_cursor(&self, graphics: &mut Graphics2D) {
+        let mouse = get_mouse_pos();
+        if swatch_at((mouse.0 as f32, mouse.1 as f32)).is_some() {
+            return;
+        }
+        let cursor = self.cursor();
+        match self.tool {
+            Tool::Tiles => {
+                if let Some(anim) = self.tiles.as_ref().and_then(|tiles| tiles.get(self.tile as usize)) {
+                    let rect = self.cell_rect(level::cell_at(cursor));
+                    anim.draw(graphics, rect, Color::from_rgba(1.0, 1.0, 1.0, 0.5), Flip::NONE);
+                }
+            }
+            Tool::PlayerStart => self.draw_marker(graphics, cursor, "", Color::from_rgba(0.0, 0.0, 1.0, 0.3)),
+            Tool::Spawns => self.draw_marker(graphics, cursor, "", Color::from_rgba(1.0, 0.0, 0.0, 0.3)),
+        }
+    }
+    /// Every tile in the tileset along the top of the screen, with the one being painted outlined
+    fn draw_palette(&self, graphics: &mut Graphics2D) {
+        let tiles = match &self.tiles {
+            Some(tiles) => tiles,
+            None => return,
+        };
+        for (index, anim) in tiles.iter().enumerate() {
+            let rect = swatch_rect(index as u16);
+            if index as u16 == self.tile && self.tool == Tool::Tiles {
+                let outline = SWATCH_GAP / 2.0;
+                graphics.draw_rectangle(
+                    Rectangle::from_tuples(
+                        (rect.top_left().x - outline, rect.top_left().y - outline),
+                        (rect.bottom_right().x + outline, rect.bottom_right().y + outline),
+                    ),
+                    Color::RED,
+                );
+            }
+            anim.draw(graphics, rect, Color::WHITE, Flip::NONE);
+        }
+    }
+    fn draw_hud(&self, graphics: &mut Graphics2D) {
+        let res = get_resolution();
+        let cell = level::cell_at(self.cursor());
+        let text = format!(
+            "{} - {} tool, cell {}, {}\nT tiles, P player start, G spawns, arrows pan, +/- zoom, S save, L reload, Esc back\n{}",
+            self.level.name, self.tool.name(), cell.0, cell.1, self.status
+        );
+        graphics.draw_text(
+            (10.0, res.1 as f32 - 80.0),
+            Color::BLACK,
+            &self.font.layout_text(&text, 20.0, TextOptions::new()),
+        );
+    }
+    fn cell_rect(&self, cell: (i32, i32)) -> Rectangle {
+        let pos = level::cell_pos(cell);
+        Rectangle::from_tuples(
+            self.camera.game_to_pix(pos),
+            self.camera.game_to_pix(pos + (TILE_SIZE, TILE_SIZE).into()),
+        )
+    }
+}
+
+/// Each tile in the tileset as its own animation, showing just that tile
+fn load_tiles(graphics: &mut Graphics2D) -> Result<Vec<Animation>, GameError> {
+    (0..level::TILESET_TILES)
+        .map(|index| {
+            let mut set = assets::load_animation_set("img/tiles.toml")?;
+            set.default = level::tile_frame(index);
+            Ok(Animation::load(graphics, "img/tiles.png", set)?)
+        })
+        .collect()
+}
+
+fn swatch_rect(index: u16) -> Rectangle {
+    let left = SWATCH_GAP + index as f32 * (SWATCH_SIZE + SWATCH_GAP);
+    Rectangle::from_tuples((left, SWATCH_GAP), (left + SWATCH_SIZE, SWATCH_GAP + SWATCH_SIZE))
+}
+
+/// The tile whose palette swatch is at `pix`, if any
+fn swatch_at(pix: (f32, f32)) -> Option<u16> {
+    (0..level::TILESET_TILES).find(|&index| {
+        let rect = swatch_rect(index);
+        let (top_left, bottom_right) = (rect.top_left(), rect.bottom_right());
+        pix.0 >= top_left.x && pix.1 >= top_left.y && pix.0 <= bottom_right.x && pix.1 <= bottom_right.y
+    })
+}
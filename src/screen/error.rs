@@ -0,0 +1,75 @@
+use speedy2d::{
+    color::Color,
+    font::{Font, TextAlignment, TextLayout, TextOptions},
+    window::{MouseButton, VirtualKeyCode, WindowHandler, WindowHelper},
+    Graphics2D,
+};
+
+use crate::{error::GameError, utility::assets};
+
+use super::{get_resolution, settings::Settings, title::TitleScreen, Screen};
+
+/// Shown in place of a screen that failed to load
+///
+/// Any key or click goes back to the title screen
+pub struct ErrorScreen {
+    new_screen: Option<Box<dyn Screen>>,
+    message: String,
+    /// None if the font is what failed to load, in which case the error only goes to stderr
+    font: Option<Font>,
+    settings: Settings,
+}
+
+impl WindowHandler<String> for ErrorScreen {
+    fn on_draw(&mut self, helper: &mut WindowHelper<String>, graphics: &mut Graphics2D) {
+        graphics.clear_screen(Color::from_rgb(0.3, 0.0, 0.0));
+
+        if let Some(font) = &self.font {
+            let res = get_resolution();
+            let text = format!(
+                "Something went wrong\n\n{}\n\nPress any key to go back",
+                self.message
+            );
+            let options = TextOptions::new()
+                .with_wrap_to_width(res.0 as f32 - 40.0, TextAlignment::Left);
+            graphics.draw_text((20.0, 20.0), Color::WHITE, &font.layout_text(&text, 32.0, options));
+        }
+
+        helper.request_redraw();
+    }
+    fn on_key_down(
+        &mut self,
+        _helper: &mut WindowHelper<String>,
+        _virtual_key_code: Option<VirtualKeyCode>,
+        _scancode: speedy2d::window::KeyScancode,
+    ) {
+        self.back();
+    }
+    fn on_mouse_button_down(&mut self, _helper: &mut WindowHelper<String>, _button: MouseButton) {
+        self.back();
+    }
+}
+
+impl Screen for ErrorScreen {
+    fn change_screen(&mut self) -> Option<Box<dyn Screen>> {
+        if self.new_screen.is_some() {
+            return self.new_screen.take();
+        }
+        None
+    }
+}
+
+impl ErrorScreen {
+    pub fn new(error: GameError, settings: Settings) -> ErrorScreen {
+        eprintln!("{}", error);
+        ErrorScreen {
+            new_screen: None,
+            message: error.to_string(),
+            font: assets::load_font("font/Cabal-w5j3.ttf").ok(),
+            settings,
+        }
+    }
+    fn back(&mut self) {
+        self.new_screen = Some(super::or_error(TitleScreen::new(self.settings), self.settings));
+    }
+}
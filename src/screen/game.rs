@@ -1,31 +1,75 @@
-use core::panic;
-use std::{collections::{HashMap}, ops::Sub, time::{Duration, Instant}};
+use std::{collections::{HashMap}, time::Duration};
 
 use bitflags::bitflags;
 use rand::Rng;
 use speedy2d::{
     color::Color,
+    font::{Font, TextAlignment, TextLayout, TextOptions},
+    shape::Rectangle,
     window::{VirtualKeyCode, WindowHandler, WindowHelper},
     Graphics2D,
 };
 
 use crate::{
-    entity::{goblin::Goblin, player::Player, tile::Tile, Entity},
-    utility::{animation::AnimationSelectError, serial_namer::SerialNamer},
-    world::space::GamePos,
+    entity::{
+        goblin::{
+            self,
+            ai::{AiParams, Target},
+            kind::{GoblinKind, GoblinKinds},
+            Goblin,
+        },
+        player::Player,
+        projectile::{Projectile, Team},
+        steering,
+        tile::Tile,
+        Entity,
+    },
+    error::GameError,
+    utility::{
+        animation::Animation,
+        assets,
+        clock::{Clock, ClockMode},
+        serial_namer::SerialNamer,
+    },
+    world::{
+        collision::{ColliderKind, EntityId},
+        difficulty::Difficulty,
+        director::{self, Director, Phase, SpawnOrder, SpawnPoint, Waves},
+        level::{self, Level},
+        seed,
+        space::GamePos,
+        World,
+    },
 };
 
 use super::{
-    camera::Camera, get_resolution, title::TitleScreen, Screen,
+    camera::Camera, error::ErrorScreen, get_resolution, settings::Settings, title::TitleScreen, Screen,
 };
 
-const GOBLIN_ATTACK_DIST: f32 = 5.0;
+/// Holding down pushes this many times harder than walking
+const DIVE: f32 = 23.0;
 
-const JUMP: f32 = 23.0;
+/// Downward acceleration, in units per second squared
+pub const GRAVITY: f32 = 720.0;
 
-pub const GRAVITY: f32 = 0.2;
+/// Rate velocities decay at, per second
+pub const DRAG: f32 = 6.32;
 
-pub const DRAG: f32 = 0.1;
+/// Length of a simulation step, which doesn't depend on the frame rate
+const TIMESTEP: Duration = Duration::from_micros(16_667);
+
+/// Anything that falls this far below the level's lowest tiles has gone down a gap and dies, in units
+const FALL_MARGIN: f32 = 50.0;
+
+/// How far past the edge of the screen goblins spawn, in units
+const SPAWN_MARGIN: f32 = 5.0;
+
+/// Longest frame the simulation catches up on,
+/// so a long stall doesn't turn into a burst of updates
+const MAX_FRAME: Duration = Duration::from_millis(250);
+
+/// Rate time runs at in slow motion, for watching hitboxes in the debug view
+const SLOW_MOTION: f32 = 0.25;
 
 bitflags! {
     struct Input: u8 {
@@ -43,96 +87,65 @@ pub struct GameScreen {
     background: Option<HashMap<String, Box<dyn Entity>>>,
     player: Option<Player>,
     goblins: Vec<Goblin>,
+    goblin_kinds: GoblinKinds,
+    /// How each goblin kind looks, loaded up front so goblins can be made in the middle of a step
+    goblin_anims: HashMap<String, Animation>,
+    projectiles: Vec<Projectile>,
     current_input: Input,
     camera: Camera,
     namer: SerialNamer,
-    start: Instant,
-    spawn_interval_ms: u16,
+    /// Drives the simulation, which keeps its own clock that only moves a step at a time
+    clock: Clock,
+    accumulator: Duration,
+    world: World,
+    director: Director,
+    difficulty: Difficulty,
+    font: Font,
+    show_debug: bool,
+    kills: u32,
+    level: Level,
+    /// Everything random in the run follows from this, so it's shown to make the run easy to replay
+    seed: u64,
+    settings: Settings,
+}
+
+/// A goblin decided on during a step, down to everything random about it
+struct Spawn {
+    id: EntityId,
+    kind: GoblinKind,
+    pos: GamePos,
+    ai: AiParams,
 }
 
 impl WindowHandler<String> for GameScreen {
     fn on_draw(&mut self, helper: &mut WindowHelper<String>, graphics: &mut Graphics2D) {
-        graphics.clear_screen(Color::CYAN);
+        graphics.clear_screen(self.level.background.to_color());
         if self.player.is_none() {
-            self.init_sprites(graphics);
-        }
-        self.process_timer(graphics);
-
-        if let Some(player) = &mut self.player {
-            let player_pos: GamePos;
-            if let Some(background) = &mut self.background {
-                {
-                    let current_input = self.current_input;
-
-                    player_pos = player.get_pos();
-
-                    if current_input.is_empty() {
-                        player.remove_anim();
-                    } else {
-                        let mut mvmt = if check_input(current_input, Input::LEFT) {
-                            (-1.0, 0.0)
-                        } else if check_input(current_input, Input::RIGHT) {
-                            (1.0, 0.0)
-                        } else if check_input(current_input, Input::DOWN) {
-                            (0.0, JUMP)
-                        } else {
-                            (0.0, 0.0)
-                        };
-
-                        if check_input(current_input, Input::UP) && player.get_pos().y == 0.0 {
-                            mvmt.1 = -JUMP;
-                        }
-
-                        player.accelerate(mvmt.into());
-                        if let Err(AnimationSelectError::NotFound) =
-                            player.intercept_anim(if check_input(current_input, Input::ATTACK) {
-                                "attack"
-                            } else {
-                                "move"
-                            })
-                        {
-                            panic!("No animation found");
-                        }
-                    }
-                    // This leads to the camera always being *slightly* behind the player (especially if player is moving fast)
-                    // Not too much of a problem tho and a pretty nice effect actually
-                    self.camera.pos = (player.get_pos().x, 0.0).into();
-                }
-
-                {
-                    for goblin in self.goblins.iter_mut() {
-                        let player_dist = goblin.get_pos().sub(player_pos);
-
-                        let direction = (
-                            if player_dist.x > GOBLIN_ATTACK_DIST {
-                                -1.0
-                            } else if player_dist.x < -GOBLIN_ATTACK_DIST {
-                                1.0
-                            } else {
-                                // Within attacking range: play attack animation
-                                if player_dist.magnitude() < GOBLIN_ATTACK_DIST {
-                                    goblin.velocity.x = 0.0;
-                                    goblin.attacking = true;
-                                }
-
-                                0.0
-                            },
-                            0.0,
-                        )
-                            .into();
-    
-                        goblin.accelerate(direction);
-                        goblin.draw(graphics, &self.camera);
-                    }
-
-                }
+            if let Err(e) = self.init_sprites(graphics) {
+                self.fail(e);
+                return;
+            }
+        }
+        // Real time is fed to the simulation in fixed steps, and the remainder carries over to the next frame
+        self.accumulator += self.clock.tick().min(MAX_FRAME);
+        while self.accumulator >= TIMESTEP {
+            self.world.clock.step(TIMESTEP);
+            self.update(TIMESTEP);
+            self.accumulator -= TIMESTEP;
+        }
+        if self.is_over() {
+            // Everything freezes where it was when the player died
+            if self.clock.mode() != ClockMode::Paused {
+                self.clock.set_mode(ClockMode::Paused);
+            }
+        }
 
-                for (_, background_object) in background.iter_mut() {
-                    background_object.draw(graphics, &self.camera);
-                }
+        let alpha = self.accumulator.as_secs_f32() / TIMESTEP.as_secs_f32();
+        self.draw(graphics, alpha);
+        self.draw_hud(graphics);
 
-                player.draw(graphics, &self.camera);
-            }
+        if self.show_debug {
+            self.draw_debug(graphics);
         }
         helper.request_redraw();
     }
@@ -145,8 +158,13 @@ impl WindowHandler<String> for GameScreen {
         if let Some(virtual_key_code) = virtual_key_code {
             match virtual_key_code {
                 VirtualKeyCode::Escape => {
-                    self.new_screen = Some(Box::new(TitleScreen::new()));
+                    self.new_screen = Some(super::or_error(TitleScreen::new(self.settings), self.settings));
+                }
+                VirtualKeyCode::F3 => {
+                    self.show_debug = !self.show_debug;
                 }
+                VirtualKeyCode::P => self.toggle_time(ClockMode::Paused),
+                VirtualKeyCode::F4 => self.toggle_time(ClockMode::Scaled(SLOW_MOTION)),
                 _ => {
                     self.current_input |= match virtual_key_code {
                         VirtualKeyCode::Left => Input::LEFT,
@@ -197,45 +215,401 @@ impl Screen for GameScreen {
 }
 
 impl GameScreen {
-    pub fn new() -> GameScreen {
+    /// Starts a run on the level runs start on
+    pub fn new(settings: Settings) -> Result<GameScreen, GameError> {
+        let seed = seed::run_seed();
+        GameScreen::with_level(level::load_seeded(level::start_level(), seed)?, seed, settings)
+    }
+    pub fn with_level(level: Level, seed: u64, settings: Settings) -> Result<GameScreen, GameError> {
         let res = get_resolution();
-        GameScreen {
+        let goblin_kinds: GoblinKinds = assets::load_data(goblin::kind::KINDS_PATH)?;
+        let waves: Waves = assets::load_data(director::WAVES_PATH)?;
+        if let Some(missing) = waves.kinds().find(|&kind| goblin_kinds.get(kind).is_none()) {
+            return Err(GameError::MissingGoblinKind(missing.to_string()));
+        }
+        if let Some(missing) = waves.markers().find(|&marker| level.spawn(marker).is_none()) {
+            return Err(GameError::MissingSpawn(missing.to_string()));
+        }
+        Ok(GameScreen {
             new_screen: None,
             player: None,
             background: None,
             current_input: Input { bits: 0 },
-            camera: Camera::new((0.0, 0.0).into(), res.0 as f32 / 10.0, res.1 as f32 / 10.0),
+            camera: Camera::new((0.0, 0.0), res.0 as f32 / 10.0, res.1 as f32 / 10.0),
             namer: SerialNamer::new(),
             goblins: Vec::new(),
-            start: Instant::now(),
-            spawn_interval_ms: 1_000,
-        }
+            goblin_kinds,
+            goblin_anims: HashMap::new(),
+            projectiles: Vec::new(),
+            clock: Clock::new(ClockMode::Real),
+            accumulator: Duration::ZERO,
+            world: World::new(Clock::new(ClockMode::Manual), seed),
+            director: Director::new(waves),
+            difficulty: Difficulty::new(settings.preset),
+            font: assets::load_font("font/Cabal-w5j3.ttf")?,
+            show_debug: false,
+            kills: 0,
+            level,
+            seed,
+            settings,
+        })
     }
-    fn init_sprites(&mut self, graphics: &mut Graphics2D) {
+    /// Builds the level's tiles, loads how each goblin kind looks and puts the player at its start
+    fn init_sprites(&mut self, graphics: &mut Graphics2D) -> Result<(), GameError> {
         let mut background: HashMap<String, Box<dyn Entity>> = HashMap::new();
-        
 
-        let mut r = rand::thread_rng();
+        for (cell, tile) in self.level.cells() {
+            let pos = level::cell_pos(cell).into();
+            let tile = Tile::new(graphics, level::tile_frame(tile), pos)?;
+            background.insert(self.namer.gen_name(), Box::new(tile));
+        }
+        self.world.terrain = self.level.terrain();
+
+        for kind in &self.goblin_kinds.kinds {
+            let anim = Goblin::load_animation(graphics, kind)?;
+            self.goblin_anims.insert(kind.name.clone(), anim);
+        }
+
+        let id = self.world.next_id();
+        let mut player = Player::new(graphics, id)?;
+        player.body.place(self.level.player_start);
+        self.player = Some(player);
+        self.background = Some(background);
+        Ok(())
+    }
+    /// Makes the goblin in `spawn`, with the looks loaded for its kind
+    fn make_goblin(&self, spawn: Spawn) -> Result<Goblin, GameError> {
+        let anim = self
+            .goblin_anims
+            .get(&spawn.kind.name)
+            .cloned()
+            .ok_or_else(|| GameError::MissingGoblinKind(spawn.kind.name.clone()))?;
+        let mut goblin = Goblin::new(spawn.id, &spawn.kind, spawn.ai, anim)?;
+        goblin.body.place(spawn.pos);
+        Ok(goblin)
+    }
+    /// Decides the kind, place and AI of the goblin in `order`, with the camera centered on `camera_x`,
+    /// or None if the kind or the spawn marker it asks for isn't there
+    fn decide_spawn(&mut self, order: &SpawnOrder, camera_x: f32) -> Option<Spawn> {
+        let difficulty = &self.difficulty;
+        let rng = &mut self.world.rng;
+        let kind = match &order.kind {
+            Some(name) => self.goblin_kinds.get(name),
+            None => self.goblin_kinds.pick(rng, |kind| difficulty.mix(kind.threat)),
+        }?;
+        let kind = kind.scaled(&difficulty.scaling());
+        // Goblins not spawning at a marker drop in from the top of the screen,
+        // which is where the camera will be once it catches up with this step
+        let top = -self.camera.height / 2.0;
+        let left = camera_x - self.camera.width / 2.0 - SPAWN_MARGIN - kind.size.0;
+        let right = camera_x + self.camera.width / 2.0 + SPAWN_MARGIN;
+        let pos = match &order.spawn {
+            SpawnPoint::Left => (left, top).into(),
+            SpawnPoint::Right => (right, top).into(),
+            SpawnPoint::Either if rng.gen_bool(0.5) => (left, top).into(),
+            SpawnPoint::Either => (right, top).into(),
+            SpawnPoint::At(x) => (*x, top).into(),
+            SpawnPoint::Marker(name) => self.level.spawn(name)?,
+        };
+        let ai = Goblin::roll_ai(&kind, rng);
+        Some(Spawn {
+            id: self.world.next_id(),
+            kind,
+            pos,
+            ai,
+        })
+    }
+    /// Runs the simulation forward by `dt`, without drawing anything
+    fn update(&mut self, dt: Duration) {
+        let camera_x = match &self.player {
+            Some(player) => player.body.pos.x,
+            None => return,
+        };
+
+        self.difficulty.update(dt, self.director.wave());
+        let alive = self.goblins.len();
+        let pace = self.difficulty.spawn_rate();
+        for order in self.director.update(dt, alive, pace) {
+            if let Some(spawn) = self.decide_spawn(&order, camera_x) {
+                match self.make_goblin(spawn) {
+                    Ok(goblin) => self.goblins.push(goblin),
+                    Err(e) => {
+                        self.fail(e);
+                        return;
+                    }
+                }
+            }
+        }
+
+        let player = match &mut self.player {
+            Some(player) => player,
+            None => return,
+        };
+
+        // Steering sees where everything was at the end of the last step
+        steer_player(player, self.current_input);
+        let centers: Vec<GamePos> = self.goblins.iter().map(|g| g.body.bounds().center()).collect();
+        let pushes = steering::separation(&centers, &goblin::SEPARATION);
+        for (goblin, push) in self.goblins.iter_mut().zip(pushes) {
+            let in_reach = self
+                .world
+                .overlapping(goblin.reach(), ColliderKind::Hurtbox)
+                .any(|collider| collider.owner == player.id());
+            let target = Target {
+                center: player.body.bounds().center(),
+                in_reach,
+            };
+            goblin.think(dt, &target, push, &mut self.world.rng);
+        }
+
+        self.world.begin_step();
+        for goblin in self.goblins.iter_mut() {
+            goblin.update(dt, &mut self.world);
+            if let Some(projectile) = goblin.shoot(&mut self.world) {
+                self.projectiles.push(projectile);
+            }
+        }
+        for projectile in self.projectiles.iter_mut() {
+            projectile.update(dt, &mut self.world);
+        }
+
+        if let Some(background) = &mut self.background {
+            for (_, background_object) in background.iter_mut() {
+                background_object.update(dt, &mut self.world);
+            }
+        }
+
+        player.update(dt, &mut self.world);
+        self.world.resolve_collisions();
+        self.apply_hits();
+    }
+    /// Deals damage for every hitbox that landed this step and to anything that fell down a gap,
+    /// and clears away dead goblins and spent projectiles
+    ///
+    /// Goblins only hurt the player and the player only hurts goblins,
+    /// and projectiles only hurt whichever side didn't fire them
+    fn apply_hits(&mut self) {
+        let player = match &mut self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let health = player.body.health.current();
+
+        for collision in self.world.collisions() {
+            if let Some(projectile) = self.projectiles.iter_mut().find(|p| p.id() == collision.attacker) {
+                let from = projectile.bounds().center();
+                if collision.target == player.id() {
+                    if let Some(damage) = projectile.strike(collision.target, Team::Player) {
+                        player.body.take_hit(damage, from);
+                    }
+                } else if let Some(goblin) = self.goblins.iter_mut().find(|g| g.id() == collision.target) {
+                    if let Some(damage) = projectile.strike(collision.target, Team::Goblins) {
+                        goblin.take_hit(damage, from);
+                    }
+                }
+            } else if collision.attacker == player.id() {
+                if let Some(damage) = player.strike_damage() {
+                    let from = player.body.bounds().center();
+                    if let Some(goblin) = self.goblins.iter_mut().find(|g| g.id() == collision.target) {
+                        goblin.take_hit(damage, from);
+                    }
+                }
+            } else if collision.target == player.id() {
+                let attacker = self.goblins.iter().find(|g| g.id() == collision.attacker);
+                if let Some(goblin) = attacker {
+                    if let Some(damage) = goblin.strike_damage() {
+                        player.body.take_hit(damage, goblin.body.bounds().center());
+                    }
+                }
+            }
+        }
+
+        // Falling down a gap is fatal, however much health is left
+        let fall_depth = self.level.bottom() + FALL_MARGIN;
+        if player.body.pos.y > fall_depth {
+            player.body.health.damage(player.body.health.max());
+        }
+        for goblin in self.goblins.iter_mut().filter(|goblin| goblin.body.pos.y > fall_depth) {
+            goblin.body.health.damage(goblin.body.health.max());
+        }
 
-        for i in -10..20 {
-            let display = (r.gen_range(0..4), r.gen_range(0..4));
+        let before = self.goblins.len();
+        self.goblins.retain(|goblin| !goblin.body.health.is_dead());
+        let kills = (before - self.goblins.len()) as u32;
+        self.kills += kills;
+        self.difficulty.add_kills(kills);
+        self.difficulty.add_damage_taken(health - player.body.health.current());
+        self.projectiles.retain(|projectile| !projectile.is_spent());
+    }
+    /// Switches time between `mode` and running normally, unless the run is over and time has stopped for good
+    fn toggle_time(&mut self, mode: ClockMode) {
+        if self.is_over() {
+            return;
+        }
+        if self.clock.mode() == mode {
+            self.clock.set_mode(ClockMode::Real);
+        } else {
+            self.clock.set_mode(mode);
+        }
+    }
+    /// The run ends when the player dies
+    fn is_over(&self) -> bool {
+        self.player.as_ref().is_some_and(|player| player.body.health.is_dead())
+    }
+    /// Draws everything `alpha` of the way between the last two updates
+    fn draw(&mut self, graphics: &mut Graphics2D, alpha: f32) {
+        if let Some(player) = &self.player {
+            self.camera.pos = (player.render_pos(alpha).x, 0.0).into();
+        }
 
-            background.insert(
-                self.namer.gen_name(),
-                Box::new(Tile::new(graphics, display, ((i as f32) * 5.0, 10.0))),
+        for goblin in self.goblins.iter() {
+            goblin.draw(graphics, &self.camera, alpha);
+        }
+        if let Some(background) = &self.background {
+            for (_, background_object) in background.iter() {
+                background_object.draw(graphics, &self.camera, alpha);
+            }
+        }
+        if let Some(player) = &self.player {
+            player.draw(graphics, &self.camera, alpha);
+        }
+        for projectile in self.projectiles.iter() {
+            projectile.draw(graphics, &self.camera, alpha);
+        }
+    }
+    fn draw_hud(&self, graphics: &mut Graphics2D) {
+        let player = match &self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let res = get_resolution();
+        let wave = match self.director.phase() {
+            Phase::Wave { number } => format!("Wave {}", number),
+            Phase::Intermission { next, left } => format!("Wave {} in {}", next, left.as_secs() + 1),
+        };
+        let text = format!(
+            "Health {}/{}    Kills {}    {}    Seed {}",
+            player.body.health.current(),
+            player.body.health.max(),
+            self.kills,
+            wave,
+            self.seed
+        );
+        graphics.draw_text(
+            (10.0, res.1 as f32 - 40.0),
+            Color::BLACK,
+            &self.font.layout_text(&text, 32.0, TextOptions::new()),
+        );
+
+        if self.clock.mode() == ClockMode::Paused && !self.is_over() {
+            let options = TextOptions::new()
+                .with_wrap_to_width(res.0 as f32, TextAlignment::Center);
+            graphics.draw_text(
+                (0.0, res.1 as f32 / 3.0),
+                Color::BLACK,
+                &self.font.layout_text("Paused\nPress P to carry on", 48.0, options),
+            );
+        }
+        if self.is_over() {
+            let text = format!("You died\n{} goblins slain\nPress Escape to go back", self.kills);
+            let options = TextOptions::new()
+                .with_wrap_to_width(res.0 as f32, TextAlignment::Center);
+            graphics.draw_text(
+                (0.0, res.1 as f32 / 3.0),
+                Color::BLACK,
+                &self.font.layout_text(&text, 48.0, options),
+            );
+        }
+    }
+    /// Gives up on the game and shows what went wrong
+    fn fail(&mut self, error: GameError) {
+        self.new_screen = Some(Box::new(ErrorScreen::new(error, self.settings)));
+    }
+    fn draw_debug(&self, graphics: &mut Graphics2D) {
+        for collider in self.world.colliders() {
+            let color = match collider.kind {
+                ColliderKind::Hurtbox => Color::from_rgba(0.0, 1.0, 0.0, 0.3),
+                ColliderKind::Hitbox => Color::from_rgba(1.0, 0.0, 0.0, 0.5),
+            };
+            graphics.draw_rectangle(
+                Rectangle::from_tuples(
+                    self.camera.game_to_pix(collider.rect.min),
+                    self.camera.game_to_pix(collider.rect.max),
+                ),
+                color,
+            );
+        }
+        for trigger in self.level.triggers.iter() {
+            graphics.draw_rectangle(
+                Rectangle::from_tuples(
+                    self.camera.game_to_pix(trigger.area.min),
+                    self.camera.game_to_pix(trigger.area.max),
+                ),
+                Color::from_rgba(0.0, 0.0, 1.0, 0.3),
+            );
+            let mut label = trigger.name.clone();
+            for (key, value) in trigger.properties.iter() {
+                label += &format!("\n{} = {}", key, value);
+            }
+            graphics.draw_text(
+                self.camera.game_to_pix(trigger.area.min),
+                Color::BLACK,
+                &self.font.layout_text(&label, 16.0, TextOptions::new()),
+            );
+        }
+        for goblin in self.goblins.iter() {
+            let state = format!("{} {:?}", goblin.kind(), goblin.ai_state());
+            let above = goblin.body.bounds().min - (0.0, 2.0).into();
+            graphics.draw_text(
+                self.camera.game_to_pix(above),
+                Color::BLACK,
+                &self.font.layout_text(&state, 16.0, TextOptions::new()),
             );
         }
 
-        self.player = Some(Player::new(graphics));
-        self.background = Some(background);
+        let text = format!(
+            "{}\ngoblins {}, projectiles {}, collisions {}\n{} difficulty, intensity {:.2}\n{}",
+            self.level.name,
+            self.goblins.len(),
+            self.projectiles.len(),
+            self.world.collisions().len(),
+            self.difficulty.preset.name(),
+            self.difficulty.intensity(),
+            assets::stats()
+        );
+        graphics.draw_text(
+            (10.0, 10.0),
+            Color::BLACK,
+            &self.font.layout_text(&text, 24.0, TextOptions::new()),
+        );
     }
-    fn process_timer(&mut self, graphics: &mut Graphics2D) {
-        let time_elspased = self.start.elapsed().as_millis();
-        if time_elspased > self.spawn_interval_ms as u128 {
-            self.goblins.push(Goblin::new(graphics));
+}
+
+fn steer_player(player: &mut Player, current_input: Input) {
+    if player.body.is_stunned() {
+        player.set_moving(false);
+        player.set_attacking(false);
+        return;
+    }
+    player.set_moving(!current_input.is_empty());
+    player.set_attacking(check_input(current_input, Input::ATTACK));
 
-            self.start = Instant::now().sub(Duration::from_millis((time_elspased - self.spawn_interval_ms as u128) as u64));
+    if !current_input.is_empty() {
+        let mvmt = if check_input(current_input, Input::LEFT) {
+            (-1.0, 0.0)
+        } else if check_input(current_input, Input::RIGHT) {
+            (1.0, 0.0)
+        } else if check_input(current_input, Input::DOWN) {
+            (0.0, DIVE)
+        } else {
+            (0.0, 0.0)
+        };
+
+        if check_input(current_input, Input::UP) {
+            player.jump();
         }
+
+        player.body.walk(mvmt.into());
     }
 }
 
@@ -1,4 +1,4 @@
-use std::{rc::Weak, sync::atomic::{AtomicU32, Ordering}};
+use std::sync::atomic::{AtomicU32, Ordering};
 
 use speedy2d::{
     dimen::Vector2,
@@ -6,10 +6,17 @@ use speedy2d::{
     Graphics2D,
 };
 
+use crate::error::GameError;
+
+use self::settings::Settings;
+
+pub mod error;
 pub mod game;
 pub mod title;
 pub mod camera;
 pub mod options;
+pub mod editor;
+pub mod settings;
 
 pub static MOUSE_POS: (AtomicU32, AtomicU32) = (AtomicU32::new(0), AtomicU32::new(0));
 pub static RESOLUTION: (AtomicU32, AtomicU32) = (AtomicU32::new(400), AtomicU32::new(500));
@@ -31,6 +38,14 @@ pub trait Screen: WindowHandler<String> {
     fn change_screen(&mut self) -> Option<Box<dyn Screen>>;
 }
 
+/// Boxes a freshly built screen, or an error screen if it failed to load
+pub fn or_error<S: Screen + 'static>(screen: Result<S, GameError>, settings: Settings) -> Box<dyn Screen> {
+    match screen {
+        Ok(screen) => Box::new(screen),
+        Err(error) => Box::new(error::ErrorScreen::new(error, settings)),
+    }
+}
+
 pub struct RedirectHandler {
     my_handler: Box<dyn Screen>,
 }
@@ -1,28 +1,26 @@
-use std::{
-    collections::{HashMap, HashSet},
-    sync::atomic::Ordering,
-};
+use std::collections::HashMap;
 
 use speedy2d::{
     color::Color,
-    font::{Font, TextAlignment, TextLayout, TextOptions},
-    shape::Rectangle,
-    window::{MouseButton, UserEventSender, VirtualKeyCode, WindowHandler, WindowHelper},
+    window::{MouseButton, UserEventSender, WindowHandler, WindowHelper},
     Graphics2D,
 };
 
 use crate::{
-    screen::RESOLUTION,
+    error::GameError,
     ui::{button::Button, rect::rect_from_size},
+    utility::assets,
+    world::difficulty::Preset,
 };
 
-use super::{RedirectHandler, Screen, game::GameScreen, title::TitleScreen};
+use super::{Screen, settings::Settings, title::TitleScreen};
 
 pub struct OptionsScreen<'a> {
     new_screen: Option<Box<dyn Screen>>,
     mouse_up: bool,
     buttons: HashMap<&'a str, Button<'a>>,
     user_event_sender: Option<UserEventSender<String>>,
+    settings: Settings,
 }
 
 impl<'a> WindowHandler<String> for OptionsScreen<'a> {
@@ -33,38 +31,29 @@ impl<'a> WindowHandler<String> for OptionsScreen<'a> {
 
         graphics.clear_screen(Color::BLUE);
 
-        for (name, button) in self.buttons.iter() {
+        for (_name, button) in self.buttons.iter() {
             button.draw(graphics);
         }
 
         helper.request_redraw();
     }
-    fn on_key_down(
-        &mut self,
-        helper: &mut WindowHelper<String>,
-        virtual_key_code: Option<speedy2d::window::VirtualKeyCode>,
-        scancode: speedy2d::window::KeyScancode,
-    ) {
-        if let Some(virtual_key_code) = virtual_key_code {
-            match virtual_key_code {
-                _ => (),
-            }
-        }
-    }
     fn on_mouse_button_up(
         &mut self,
-        helper: &mut WindowHelper<String>,
-        button: speedy2d::window::MouseButton,
+        _helper: &mut WindowHelper<String>,
+        _button: speedy2d::window::MouseButton,
     ) {
         self.mouse_up = true;
     }
-    fn on_mouse_button_down(&mut self, helper: &mut WindowHelper<String>, button: MouseButton) {
+    fn on_mouse_button_down(&mut self, _helper: &mut WindowHelper<String>, button: MouseButton) {
         if self.mouse_up {
             if let MouseButton::Left = button {
-                for (_, button) in self.buttons.iter() {
-                    let pos = super::get_mouse_pos();
-                    let pos = (pos.0 as f32, pos.1 as f32);
-                    button.eval_click(pos, &self.user_event_sender.as_ref().unwrap());
+                // The sender is only created on the first draw
+                if let Some(sender) = &self.user_event_sender {
+                    for (_, button) in self.buttons.iter() {
+                        let pos = super::get_mouse_pos();
+                        let pos = (pos.0 as f32, pos.1 as f32);
+                        button.eval_click(pos, sender);
+                    }
                 }
             }
         }
@@ -72,7 +61,7 @@ impl<'a> WindowHandler<String> for OptionsScreen<'a> {
     }
     fn on_resize(
         &mut self,
-        helper: &mut WindowHelper<String>,
+        _helper: &mut WindowHelper<String>,
         size_pixels: speedy2d::dimen::Vector2<u32>,
     ) {
         super::set_resolution(size_pixels.x, size_pixels.y);
@@ -80,28 +69,27 @@ impl<'a> WindowHandler<String> for OptionsScreen<'a> {
         let res = super::get_resolution();
         let center = (res.0 / 2, res.1 / 2);
         for (name, button) in self.buttons.iter_mut() {
-            button.set_bounds(rect_from_size(
-                button.width(),
-                button.height(),
-                match *name {
-                    "back" => (center.0, center.1 + 160),
-                    _ => panic!("Not implemented button center scheme!!")
-                },
-            ));
+            let pos = match Preset::ALL.iter().find(|preset| preset.name() == *name) {
+                Some(&preset) => preset_pos(center, preset),
+                None if *name == "back" => (center.0, center.1 + 160),
+                // Buttons without a layout here stay where they are
+                None => continue,
+            };
+            button.set_bounds(rect_from_size(button.width(), button.height(), pos));
         }
     }
     fn on_start(
         &mut self,
-        helper: &mut WindowHelper<String>,
-        info: speedy2d::window::WindowStartupInfo,
+        _helper: &mut WindowHelper<String>,
+        _info: speedy2d::window::WindowStartupInfo,
     ) {
     }
-    fn on_user_event(&mut self, helper: &mut WindowHelper<String>, user_event: String) {
-        match &user_event[..] {
-            "back" => {
-                self.new_screen = Some(Box::new(TitleScreen::new()));
-            },
-            _ => (),
+    fn on_user_event(&mut self, _helper: &mut WindowHelper<String>, user_event: String) {
+        if &user_event[..] == "back" {
+            self.new_screen = Some(super::or_error(TitleScreen::new(self.settings), self.settings));
+        } else if let Some(&preset) = Preset::ALL.iter().find(|preset| preset.name() == user_event) {
+            self.settings.preset = preset;
+            self.highlight_preset();
         }
     }
 }
@@ -116,21 +104,39 @@ impl<'a> Screen for OptionsScreen<'a> {
 }
 
 impl<'a> OptionsScreen<'a> {
-    pub fn new() -> OptionsScreen<'a> {
-        let font = Font::new(include_bytes!("../../assets/font/Cabal-w5j3.ttf")).unwrap();
+    pub fn new(settings: Settings) -> Result<OptionsScreen<'a>, GameError> {
+        let font = assets::load_font("font/Cabal-w5j3.ttf")?;
 
         let mut buttons = HashMap::new();
 
         let res = super::get_resolution();
 
         let center = (res.0 / 2, res.1 / 2);
+        for preset in Preset::ALL.iter().copied() {
+            buttons.insert(
+                preset.name(),
+                Button::new(
+                    preset.name(),
+                    64.0,
+                    Box::new(move |s: &UserEventSender<String>| {
+                        let _ = s.send_event(String::from(preset.name()));
+                    }),
+                    250,
+                    60,
+                    preset_pos(center, preset),
+                    Color::WHITE,
+                    Color::BLACK,
+                    font.clone(),
+                ),
+            );
+        }
         buttons.insert(
             "back",
             Button::new(
                 "Back",
                 64.0,
                 Box::new(|s: &UserEventSender<String>| {
-                    s.send_event(String::from("back")).unwrap();
+                    let _ = s.send_event(String::from("back"));
                 }),
                 180,
                 60,
@@ -141,11 +147,32 @@ impl<'a> OptionsScreen<'a> {
             ),
         );
 
-        OptionsScreen {
+        let mut screen = OptionsScreen {
             new_screen: None,
             mouse_up: true,
             buttons,
             user_event_sender: None,
+            settings,
+        };
+        screen.highlight_preset();
+        Ok(screen)
+    }
+    /// Shows which difficulty preset new runs use by inverting its button
+    fn highlight_preset(&mut self) {
+        for preset in Preset::ALL.iter().copied() {
+            if let Some(button) = self.buttons.get_mut(preset.name()) {
+                if preset == self.settings.preset {
+                    button.set_colors(Color::BLACK, Color::WHITE);
+                } else {
+                    button.set_colors(Color::WHITE, Color::BLACK);
+                }
+            }
         }
     }
 }
+
+/// Difficulty presets are stacked above the back button, easiest first
+fn preset_pos(center: (u32, u32), preset: Preset) -> (u32, u32) {
+    let index = Preset::ALL.iter().position(|&p| p == preset).unwrap_or(0) as u32;
+    (center.0, (center.1 + 80 * index).saturating_sub(80))
+}
@@ -0,0 +1,16 @@
+use crate::world::difficulty::Preset;
+
+/// Choices made on the options screen, which each screen hands on to the next
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Settings {
+    /// Difficulty new runs start on
+    pub preset: Preset,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            preset: Preset::Normal,
+        }
+    }
+}
@@ -1,28 +1,25 @@
-use std::{
-    collections::{HashMap, HashSet},
-    sync::atomic::Ordering,
-};
+use std::collections::HashMap;
 
 use speedy2d::{
     color::Color,
-    font::{Font, TextAlignment, TextLayout, TextOptions},
-    shape::Rectangle,
-    window::{MouseButton, UserEventSender, VirtualKeyCode, WindowHandler, WindowHelper},
+    window::{MouseButton, UserEventSender, WindowHandler, WindowHelper},
     Graphics2D,
 };
 
 use crate::{
-    screen::RESOLUTION,
+    error::GameError,
     ui::{button::Button, rect::rect_from_size},
+    utility::assets,
 };
 
-use super::{RedirectHandler, Screen, game::GameScreen, options::OptionsScreen};
+use super::{Screen, editor::EditorScreen, game::GameScreen, options::OptionsScreen, settings::Settings};
 
 pub struct TitleScreen<'a> {
     new_screen: Option<Box<dyn Screen>>,
     mouse_up: bool,
     buttons: HashMap<&'a str, Button<'a>>,
     user_event_sender: Option<UserEventSender<String>>,
+    settings: Settings,
 }
 
 impl<'a> WindowHandler<String> for TitleScreen<'a> {
@@ -33,38 +30,29 @@ impl<'a> WindowHandler<String> for TitleScreen<'a> {
 
         graphics.clear_screen(Color::BLUE);
 
-        for (name, button) in self.buttons.iter() {
+        for (_name, button) in self.buttons.iter() {
             button.draw(graphics);
         }
 
         helper.request_redraw();
     }
-    fn on_key_down(
-        &mut self,
-        helper: &mut WindowHelper<String>,
-        virtual_key_code: Option<speedy2d::window::VirtualKeyCode>,
-        scancode: speedy2d::window::KeyScancode,
-    ) {
-        if let Some(virtual_key_code) = virtual_key_code {
-            match virtual_key_code {
-                _ => (),
-            }
-        }
-    }
     fn on_mouse_button_up(
         &mut self,
-        helper: &mut WindowHelper<String>,
-        button: speedy2d::window::MouseButton,
+        _helper: &mut WindowHelper<String>,
+        _button: speedy2d::window::MouseButton,
     ) {
         self.mouse_up = true;
     }
-    fn on_mouse_button_down(&mut self, helper: &mut WindowHelper<String>, button: MouseButton) {
+    fn on_mouse_button_down(&mut self, _helper: &mut WindowHelper<String>, button: MouseButton) {
         if self.mouse_up {
             if let MouseButton::Left = button {
-                for (_, button) in self.buttons.iter() {
-                    let pos = super::get_mouse_pos();
-                    let pos = (pos.0 as f32, pos.1 as f32);
-                    button.eval_click(pos, &self.user_event_sender.as_ref().unwrap());
+                // The sender is only created on the first draw
+                if let Some(sender) = &self.user_event_sender {
+                    for (_, button) in self.buttons.iter() {
+                        let pos = super::get_mouse_pos();
+                        let pos = (pos.0 as f32, pos.1 as f32);
+                        button.eval_click(pos, sender);
+                    }
                 }
             }
         }
@@ -72,7 +60,7 @@ impl<'a> WindowHandler<String> for TitleScreen<'a> {
     }
     fn on_resize(
         &mut self,
-        helper: &mut WindowHelper<String>,
+        _helper: &mut WindowHelper<String>,
         size_pixels: speedy2d::dimen::Vector2<u32>,
     ) {
         super::set_resolution(size_pixels.x, size_pixels.y);
@@ -80,31 +68,33 @@ impl<'a> WindowHandler<String> for TitleScreen<'a> {
         let res = super::get_resolution();
         let center = (res.0 / 2, res.1 / 2);
         for (name, button) in self.buttons.iter_mut() {
-            button.set_bounds(rect_from_size(
-                button.width(),
-                button.height(),
-                match *name {
-                    "start" => (center.0, center.1),
-                    "options" => (center.0, center.1 + 80),
-                    "quit" => (center.0, center.1 + 160),
-                    _ => panic!("Not implemented button center scheme!!")
-                },
-            ));
+            let pos = match *name {
+                "start" => (center.0, center.1),
+                "options" => (center.0, center.1 + 80),
+                "editor" => (center.0, center.1 + 160),
+                "quit" => (center.0, center.1 + 240),
+                // Buttons without a layout here stay where they are
+                _ => continue,
+            };
+            button.set_bounds(rect_from_size(button.width(), button.height(), pos));
         }
     }
     fn on_start(
         &mut self,
-        helper: &mut WindowHelper<String>,
-        info: speedy2d::window::WindowStartupInfo,
+        _helper: &mut WindowHelper<String>,
+        _info: speedy2d::window::WindowStartupInfo,
     ) {
     }
     fn on_user_event(&mut self, helper: &mut WindowHelper<String>, user_event: String) {
         match &user_event[..] {
             "start" => {
-                self.new_screen = Some(Box::new(GameScreen::new()));
+                self.new_screen = Some(super::or_error(GameScreen::new(self.settings), self.settings));
             },
             "options" => {
-                self.new_screen = Some(Box::new(OptionsScreen::new()));
+                self.new_screen = Some(super::or_error(OptionsScreen::new(self.settings), self.settings));
+            },
+            "editor" => {
+                self.new_screen = Some(super::or_error(EditorScreen::new(self.settings), self.settings));
             },
             "quit" => {
                 helper.terminate_loop();
@@ -124,8 +114,8 @@ impl<'a> Screen for TitleScreen<'a> {
 }
 
 impl<'a> TitleScreen<'a> {
-    pub fn new() -> TitleScreen<'a> {
-        let font = Font::new(include_bytes!("../../assets/font/Cabal-w5j3.ttf")).unwrap();
+    pub fn new(settings: Settings) -> Result<TitleScreen<'a>, GameError> {
+        let font = assets::load_font("font/Cabal-w5j3.ttf")?;
 
         let mut buttons = HashMap::new();
 
@@ -139,7 +129,7 @@ impl<'a> TitleScreen<'a> {
                 "Start",
                 64.0,
                 Box::new(|s: &UserEventSender<String>| {
-                    s.send_event(String::from("start")).unwrap();
+                    let _ = s.send_event(String::from("start"));
                 }),
                 250,
                 60,
@@ -155,7 +145,7 @@ impl<'a> TitleScreen<'a> {
                 "Options",
                 64.0,
                 Box::new(|s: &UserEventSender<String>| {
-                    s.send_event(String::from("options")).unwrap();
+                    let _ = s.send_event(String::from("options"));
                 }),
                 250,
                 60,
@@ -165,28 +155,45 @@ impl<'a> TitleScreen<'a> {
                 font.clone(),
             ),
         );
+        buttons.insert(
+            "editor",
+            Button::new(
+                "Editor",
+                64.0,
+                Box::new(|s: &UserEventSender<String>| {
+                    let _ = s.send_event(String::from("editor"));
+                }),
+                250,
+                60,
+                (center.0, center.1 + 160),
+                Color::WHITE,
+                Color::BLACK,
+                font.clone(),
+            ),
+        );
         buttons.insert(
             "quit",
             Button::new(
                 "Quit",
                 64.0,
                 Box::new(|s: &UserEventSender<String>| {
-                    s.send_event(String::from("quit")).unwrap();
+                    let _ = s.send_event(String::from("quit"));
                 }),
                 250,
                 60,
-                (center.0, center.1 + 160),
+                (center.0, center.1 + 240),
                 Color::WHITE,
                 Color::BLACK,
                 font,
             ),
         );
 
-        TitleScreen {
+        Ok(TitleScreen {
             new_screen: None,
             mouse_up: true,
             buttons,
             user_event_sender: None,
-        }
+            settings,
+        })
     }
 }
@@ -1,13 +1,13 @@
-use std::borrow::BorrowMut;
-
 use speedy2d::{Graphics2D, color::Color, font::{Font, TextAlignment, TextLayout, TextOptions}, shape::Rectangle, window::UserEventSender};
 
 use super::rect::rect_from_size;
 
+type ClickHandler = Box<dyn Fn(&UserEventSender<String>)>;
+
 pub struct Button<'a> {
     text: &'a str,
     font_size: f32,
-    on_click: Box<dyn Fn(&UserEventSender<String>)>,
+    on_click: ClickHandler,
     bounds: Rectangle,
     background: Color,
     foreground: Color,
@@ -15,6 +15,7 @@ pub struct Button<'a> {
 }
 
 impl<'a> Button<'a> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new<T: Fn(&UserEventSender<String>) + 'static>(
         text: &'a str,
         font_size: f32,
@@ -52,6 +53,10 @@ impl<'a> Button<'a> {
             ),
         );
     }
+    pub fn set_colors(&mut self, background: Color, foreground: Color) {
+        self.background = background;
+        self.foreground = foreground;
+    }
     pub fn set_bounds(&mut self, new_bounds: Rectangle) {
         self.bounds = new_bounds;
     }
@@ -1,64 +1,223 @@
-use std::{collections::HashMap, time::Instant};
+use std::{collections::HashMap, time::Duration};
 
+use bitflags::bitflags;
 use speedy2d::{
     color::Color,
-    image::{ImageDataType, ImageHandle},
+    image::ImageHandle,
     shape::Rectangle,
     Graphics2D,
 };
 
+use crate::world::collision::ColliderKind;
+
+use super::{
+    animation_set::{AnimationLoadError, AnimationSet},
+    assets::{self, AssetError},
+    clock::Clock,
+    sheet::Rgb,
+};
+
 #[derive(Debug)]
 pub enum AnimationSelectError {
     AlreadyPlaying,
     NotFound,
 }
 
+bitflags! {
+    /// Mirrors a frame when drawing it
+    pub struct Flip: u8 {
+        const NONE       = 0b00;
+        const HORIZONTAL = 0b01;
+        const VERTICAL   = 0b10;
+    }
+}
+
+/// `(x, y, width, height)` in pixels, relative to the top left of a frame
+pub type FrameRect = (u16, u16, u16, u16);
+
+/// A single frame of a clip
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// (column, row) of the frame in the sheet
+    pub pos: (u16, u16),
+    pub duration_ms: u16,
+    /// Names of the events fired when this frame is reached
+    pub events: Vec<String>,
+    pub boxes: Vec<(ColliderKind, FrameRect)>,
+}
+
+/// A named sequence of frames
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameLoop {
+    pub looping: bool,
+    pub frames: Vec<Frame>,
+}
+
+impl FrameLoop {
+    fn duration_ms(&self) -> u128 {
+        self.frames.iter().map(|f| f.duration_ms as u128).sum()
+    }
+    /// Which frame is showing `elapsed_ms` into the clip, counting frames from previous loops too
+    fn step_at(&self, elapsed_ms: u128) -> u128 {
+        let total = self.duration_ms();
+        if total == 0 {
+            return 0;
+        }
+        let mut offset = elapsed_ms % total;
+        let mut index = 0;
+        for frame in &self.frames {
+            if offset < frame.duration_ms as u128 {
+                break;
+            }
+            offset -= frame.duration_ms as u128;
+            index += 1;
+        }
+        (elapsed_ms / total) * self.frames.len() as u128 + index
+    }
+    /// Moves on from step `last` to `elapsed_ms` into the clip,
+    /// adding the events of every frame reached on the way to `events`, and returns the new step
+    ///
+    /// A clip that has only just started (`last` is `None`) only fires the frame it starts on,
+    /// and a long stall never replays more than one loop of events
+    fn advance(&self, last: Option<u128>, elapsed_ms: u128, events: &mut Vec<String>) -> u128 {
+        let len = self.frames.len() as u128;
+        let step = self.step_at(elapsed_ms);
+        let first = match last {
+            Some(last) => (last + 1).max(step.saturating_sub(len - 1)),
+            None => step,
+        };
+        let last = if self.looping { step } else { step.min(len - 1) };
+        for i in first..=last {
+            let frame = &self.frames[(i % len) as usize];
+            events.extend(frame.events.iter().cloned());
+        }
+        step
+    }
+}
+
+/// Describes how frames are laid out in a sprite sheet
+///
+/// Each frame is surrounded by `border` pixels of padding on every side,
+/// padded cells are separated by `spacing` pixels,
+/// and the whole grid starts `offset` pixels from the top left of the image
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SheetLayout {
+    pub frame_size: (u16, u16),
+    pub border: u16,
+    pub spacing: u16,
+    pub offset: (u16, u16),
+}
+
+impl SheetLayout {
+    pub fn new(frame_size: (u16, u16)) -> SheetLayout {
+        SheetLayout {
+            frame_size,
+            border: 0,
+            spacing: 0,
+            offset: (0, 0),
+        }
+    }
+    pub fn with_border(mut self, border: u16) -> SheetLayout {
+        self.border = border;
+        self
+    }
+    /// Pixel position of the top left of the frame at (column, row), excluding its border
+    pub fn frame_origin(&self, pos: (u16, u16)) -> (u32, u32) {
+        let stride = self.stride();
+        (
+            self.offset.0 as u32 + self.border as u32 + pos.0 as u32 * stride.0,
+            self.offset.1 as u32 + self.border as u32 + pos.1 as u32 * stride.1,
+        )
+    }
+    /// Number of whole (columns, rows) of frames in a sheet of the given pixel size
+    pub fn frame_count(&self, sheet_size: (u32, u32)) -> (u32, u32) {
+        let stride = self.stride();
+        let usable = (
+            (sheet_size.0 + self.spacing as u32).saturating_sub(self.offset.0 as u32),
+            (sheet_size.1 + self.spacing as u32).saturating_sub(self.offset.1 as u32),
+        );
+        (usable.0 / stride.0, usable.1 / stride.1)
+    }
+    /// Distance in pixels from one frame to the next along each axis
+    pub fn stride(&self) -> (u32, u32) {
+        let padding = 2 * self.border as u32 + self.spacing as u32;
+        (
+            self.frame_size.0 as u32 + padding,
+            self.frame_size.1 as u32 + padding,
+        )
+    }
+}
+
+#[derive(Clone)]
 pub struct Animation {
     src: ImageHandle,
-    frame_size: (u16, u16),
-    frames: HashMap<&'static str, (bool, Vec<(u16, u16)>)>,
+    layout: SheetLayout,
+    frames: HashMap<String, FrameLoop>,
     default: (u16, u16),
-    pub frame_loop: Option<(bool, Vec<(u16, u16)>)>,
-    start: Instant,
-    iter_speed_ms: u16,
+    hurtbox: FrameRect,
+    pub frame_loop: Option<FrameLoop>,
+    /// Game time the current clip started at
+    start: Duration,
+    /// Frames played since the clip started, including those from previous loops
+    step: Option<u128>,
+    events: Vec<String>,
 }
 impl Animation {
-    pub fn new(
-        src: ImageHandle,
-        frame_size: (u16, u16),
-        frames: HashMap<&'static str, (bool, Vec<(u16, u16)>)>,
-        default: (u16, u16),
-        iter_speed_ms: u16,
-    ) -> Animation {
-        Animation {
+    /// Builds an animation over `src` from the clips in `set`,
+    /// checking that every frame actually exists in the sheet
+    pub fn new(src: ImageHandle, set: AnimationSet) -> Result<Animation, AnimationLoadError> {
+        let sheet_size = (src.size().x, src.size().y);
+        let layout = set.layout();
+        let default = set.default;
+        let hurtbox = set
+            .hurtbox
+            .unwrap_or((0, 0, layout.frame_size.0, layout.frame_size.1));
+        let frames = set.into_frames(sheet_size)?;
+        Ok(Animation {
             src,
-            frame_size,
+            layout,
             frames,
             default,
+            hurtbox,
             frame_loop: None,
-            start: Instant::now(),
-            iter_speed_ms,
-        }
+            start: Duration::ZERO,
+            step: None,
+            events: Vec::new(),
+        })
     }
-    pub fn select(&mut self, anim: &str) -> Result<(), AnimationSelectError> {
-        match self.frames.get(anim) {
-            Some(frames) => {
-                if Some(frames) == self.frame_loop.as_ref() {
-                    return Err(AnimationSelectError::AlreadyPlaying)
-                }
-                self.start = Instant::now();
-                self.frame_loop = Some(frames.clone());
-                Ok(())
-            }
-            None => Err(AnimationSelectError::NotFound),
-        }
+    /// Builds an animation from `set` over the sheet at `image_path`,
+    /// which is replaced with a checkerboard if it can't be loaded
+    pub fn load(
+        graphics: &mut Graphics2D,
+        image_path: &str,
+        set: AnimationSet,
+    ) -> Result<Animation, AssetError> {
+        Animation::load_swapped(graphics, image_path, &[], set)
+    }
+    /// Like `load`, with the sheet's colours swapped according to `palette`
+    pub fn load_swapped(
+        graphics: &mut Graphics2D,
+        image_path: &str,
+        palette: &[(Rgb, Rgb)],
+        set: AnimationSet,
+    ) -> Result<Animation, AssetError> {
+        let src = assets::load_image_or_missing(graphics, image_path, palette, set.sheet_size())?;
+        Animation::new(src, set).map_err(|error| AssetError::Animation {
+            path: image_path.to_string(),
+            error,
+        })
     }
-    pub fn intercept(&mut self, anim: &str) -> Result<(), AnimationSelectError> {
+    pub fn has_clip(&self, anim: &str) -> bool {
+        self.frames.contains_key(anim)
+    }
+    pub fn select(&mut self, anim: &str, clock: &Clock) -> Result<(), AnimationSelectError> {
         match self.frames.get(anim) {
             Some(frames) => {
                 if Some(frames) == self.frame_loop.as_ref() {
                     return Err(AnimationSelectError::AlreadyPlaying)
                 }
+                self.start = clock.now();
+                self.step = None;
                 self.frame_loop = Some(frames.clone());
                 Ok(())
             }
@@ -67,42 +226,211 @@ impl Animation {
     }
     pub fn deselect(&mut self) {
         self.frame_loop = None;
+        self.step = None;
     }
-    pub fn draw(&mut self, graphics: &mut Graphics2D, window_rect: Rectangle<f32>, color: Color) {
-        let frame_pos = match &self.frame_loop {
-            Some((do_loop, frame_loop)) => {
-                let duration_ms = self.start.elapsed().as_millis();
-                let frame_count = duration_ms / self.iter_speed_ms as u128;
-                if !do_loop && frame_count > frame_loop.len() as u128 {
-                    self.deselect();
-                    self.default
-                }
-                else {
-                    let frame_offset = (frame_count % frame_loop.len() as u128) as usize;
+    /// Advances the current clip, collecting the events of every frame reached since the last update
+    ///
+    /// Events from the previous update are discarded, so poll them with `events` after each call
+    pub fn update(&mut self, clock: &Clock) {
+        self.events.clear();
+        let frame_loop = match &self.frame_loop {
+            Some(frame_loop) => frame_loop,
+            None => return,
+        };
+        let elapsed_ms = (clock.now() - self.start).as_millis();
+        let step = frame_loop.advance(self.step, elapsed_ms, &mut self.events);
 
-                    frame_loop[frame_offset]
-                }
-            }
+        if !frame_loop.looping && step >= frame_loop.frames.len() as u128 {
+            self.deselect();
+        } else {
+            self.step = Some(step);
+        }
+    }
+    /// Events fired during the last `update`
+    pub fn events(&self) -> &[String] {
+        &self.events
+    }
+    pub fn draw(
+        &self,
+        graphics: &mut Graphics2D,
+        window_rect: Rectangle<f32>,
+        color: Color,
+        flip: Flip,
+    ) {
+        let frame_pos = match self.current_frame() {
+            Some(frame) => frame.pos,
             None => self.default,
         };
 
         graphics.draw_rectangle_image_subset_tinted(
             window_rect,
             color,
-            self.get_bounds_rect_from_pos(frame_pos),
+            self.get_bounds_rect_from_pos(frame_pos, flip),
             &self.src,
         );
     }
-    fn get_bounds_rect_from_pos(&self, pos: (u16, u16)) -> Rectangle {
+    pub fn frame_size(&self) -> (u16, u16) {
+        self.layout.frame_size
+    }
+    /// Boxes of `kind` on the frame showing right now, falling back to the default hurtbox
+    pub fn boxes(&self, kind: ColliderKind) -> Vec<FrameRect> {
+        let mut boxes: Vec<FrameRect> = match self.current_frame() {
+            Some(frame) => frame
+                .boxes
+                .iter()
+                .filter(|(k, _)| *k == kind)
+                .map(|(_, rect)| *rect)
+                .collect(),
+            None => Vec::new(),
+        };
+        if boxes.is_empty() && kind == ColliderKind::Hurtbox {
+            boxes.push(self.hurtbox);
+        }
+        boxes
+    }
+    fn current_frame(&self) -> Option<&Frame> {
+        match (&self.frame_loop, self.step) {
+            (Some(frame_loop), Some(step)) => {
+                Some(&frame_loop.frames[(step % frame_loop.frames.len() as u128) as usize])
+            }
+            _ => None,
+        }
+    }
+    fn get_bounds_rect_from_pos(&self, pos: (u16, u16), flip: Flip) -> Rectangle {
         let img_bounds = self.src.size();
+        let origin = self.layout.frame_origin(pos);
         let top_left = (
-            (pos.0 as f32 * self.frame_size.0 as f32) / (img_bounds.x as f32),
-            (pos.1 as f32 * self.frame_size.1 as f32) / (img_bounds.y as f32),
+            origin.0 as f32 / img_bounds.x as f32,
+            origin.1 as f32 / img_bounds.y as f32,
         );
         let bottom_right = (
-            ((pos.0 + 1) as f32 * self.frame_size.0 as f32) / (img_bounds.x as f32),
-            ((pos.1 + 1) as f32 * self.frame_size.1 as f32) / (img_bounds.y as f32),
+            (origin.0 + self.layout.frame_size.0 as u32) as f32 / img_bounds.x as f32,
+            (origin.1 + self.layout.frame_size.1 as u32) as f32 / img_bounds.y as f32,
         );
-        Rectangle::from_tuples(top_left, bottom_right)
+        // Swapping the image coordinates mirrors the frame across the drawn rectangle
+        let (left, right) = if flip.contains(Flip::HORIZONTAL) {
+            (bottom_right.0, top_left.0)
+        } else {
+            (top_left.0, bottom_right.0)
+        };
+        let (top, bottom) = if flip.contains(Flip::VERTICAL) {
+            (bottom_right.1, top_left.1)
+        } else {
+            (top_left.1, bottom_right.1)
+        };
+        Rectangle::from_tuples((left, top), (right, bottom))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::clock::ClockMode, *};
+
+    fn frame(duration_ms: u16, events: &[&str]) -> Frame {
+        Frame {
+            pos: (0, 0),
+            duration_ms,
+            events: events.iter().map(|e| e.to_string()).collect(),
+            boxes: Vec::new(),
+        }
+    }
+
+    /// A short wind-up, a quick strike that fires `hit`, and a long recovery
+    fn attack(looping: bool) -> FrameLoop {
+        FrameLoop {
+            looping,
+            frames: vec![frame(100, &[]), frame(50, &["hit"]), frame(200, &[])],
+        }
+    }
+
+    #[test]
+    fn step_at_follows_each_frames_duration() {
+        let clip = attack(true);
+        assert_eq!(clip.step_at(0), 0);
+        assert_eq!(clip.step_at(99), 0);
+        assert_eq!(clip.step_at(100), 1);
+        assert_eq!(clip.step_at(149), 1);
+        assert_eq!(clip.step_at(150), 2);
+        assert_eq!(clip.step_at(349), 2);
+    }
+
+    #[test]
+    fn step_at_counts_previous_loops() {
+        let clip = attack(true);
+        assert_eq!(clip.step_at(350), 3);
+        assert_eq!(clip.step_at(450), 4);
+        assert_eq!(clip.step_at(3 * 350 + 160), 3 * 3 + 2);
+    }
+
+    #[test]
+    fn manual_clock_drives_frames() {
+        let clip = attack(true);
+        let mut clock = Clock::new(ClockMode::Manual);
+        let mut frames = Vec::new();
+        for _ in 0..8 {
+            frames.push(clip.step_at(clock.now().as_millis()) % 3);
+            clock.step(Duration::from_millis(50));
+        }
+        // Two steps of wind-up, one of the strike, four of recovery, then round again
+        assert_eq!(frames, vec![0, 0, 1, 2, 2, 2, 2, 0]);
+    }
+
+    #[test]
+    fn skipped_frames_fire_their_events_once() {
+        let clip = attack(false);
+        let mut events = Vec::new();
+        let step = clip.advance(None, 0, &mut events);
+        assert!(events.is_empty());
+
+        // One long update jumps from the wind-up straight to the recovery
+        let step = clip.advance(Some(step), 200, &mut events);
+        assert_eq!(step, 2);
+        assert_eq!(events, vec![String::from("hit")]);
+
+        events.clear();
+        clip.advance(Some(step), 300, &mut events);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn long_stalls_fire_events_at_most_once_per_loop() {
+        let clip = attack(true);
+        let mut events = Vec::new();
+        let step = clip.advance(None, 0, &mut events);
+        clip.advance(Some(step), 10 * 350, &mut events);
+        assert_eq!(events, vec![String::from("hit")]);
+    }
+
+    #[test]
+    fn finished_clips_fire_nothing_past_their_end() {
+        let clip = attack(false);
+        let mut events = Vec::new();
+        let step = clip.advance(None, 120, &mut events);
+        assert_eq!(events, vec![String::from("hit")]);
+
+        events.clear();
+        clip.advance(Some(step), 2000, &mut events);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn frame_origin_skips_offset_border_and_spacing() {
+        let layout = SheetLayout {
+            frame_size: (10, 8),
+            border: 1,
+            spacing: 2,
+            offset: (3, 4),
+        };
+        assert_eq!(layout.stride(), (14, 12));
+        assert_eq!(layout.frame_origin((0, 0)), (4, 5));
+        assert_eq!(layout.frame_origin((1, 0)), (18, 5));
+        assert_eq!(layout.frame_origin((2, 3)), (32, 41));
+    }
+
+    #[test]
+    fn frame_origin_without_padding_is_a_plain_grid() {
+        let layout = SheetLayout::new((16, 16));
+        assert_eq!(layout.frame_origin((0, 0)), (0, 0));
+        assert_eq!(layout.frame_origin((3, 2)), (48, 32));
     }
 }
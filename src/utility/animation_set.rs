@@ -0,0 +1,413 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use serde::Deserialize;
+
+use crate::world::collision::ColliderKind;
+
+use super::animation::{Frame, FrameLoop, FrameRect, SheetLayout};
+
+#[derive(Debug)]
+pub enum AnimationLoadError {
+    Parse(toml::de::Error),
+    /// Frames with no width or height, which no sheet can be cut into
+    ZeroFrameSize((u16, u16)),
+    DuplicateClip(String),
+    EmptyClip(String),
+    /// A clip whose frames all last no time, which would never move past its first frame
+    ZeroDuration(String),
+    FrameOutOfBounds { clip: String, frame: (u16, u16) },
+    DurationCount { clip: String, expected: usize, found: usize },
+    EventOutOfBounds { clip: String, event: String, index: usize },
+    BoxOutOfBounds { clip: String, index: usize },
+}
+
+impl fmt::Display for AnimationLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnimationLoadError::Parse(e) => write!(f, "invalid animation file: {}", e),
+            AnimationLoadError::ZeroFrameSize(size) => {
+                write!(f, "frame_size {:?} has no area, so it can't be cut from a sheet", size)
+            }
+            AnimationLoadError::DuplicateClip(name) => {
+                write!(f, "clip \"{}\" is defined more than once", name)
+            }
+            AnimationLoadError::EmptyClip(name) => write!(f, "clip \"{}\" has no frames", name),
+            AnimationLoadError::ZeroDuration(name) => {
+                write!(f, "clip \"{}\" takes no time, so it would never play past its first frame", name)
+            }
+            AnimationLoadError::FrameOutOfBounds { clip, frame } => write!(
+                f,
+                "clip \"{}\" uses frame {:?}, which is outside the sprite sheet",
+                clip, frame
+            ),
+            AnimationLoadError::DurationCount { clip, expected, found } => write!(
+                f,
+                "clip \"{}\" has {} frames but {} durations",
+                clip, expected, found
+            ),
+            AnimationLoadError::EventOutOfBounds { clip, event, index } => write!(
+                f,
+                "event \"{}\" in clip \"{}\" is on frame {}, which the clip does not have",
+                event, clip, index
+            ),
+            AnimationLoadError::BoxOutOfBounds { clip, index } => write!(
+                f,
+                "a box in clip \"{}\" is on frame {}, which the clip does not have",
+                clip, index
+            ),
+        }
+    }
+}
+
+impl From<toml::de::Error> for AnimationLoadError {
+    fn from(e: toml::de::Error) -> Self {
+        AnimationLoadError::Parse(e)
+    }
+}
+
+/// The contents of an animation file, which sits next to the sprite sheet it describes
+///
+/// ```toml
+/// frame_size = [10, 10]
+/// border = 1
+/// speed_ms = 100
+///
+/// [[clip]]
+/// name = "move right"
+/// frames = [[0, 0], [1, 0], [2, 0], [3, 0]]
+/// events = { footstep = [0, 2] }
+///
+/// [[clip]]
+/// name = "attack right"
+/// loop = false
+/// frames = [[0, 2], [1, 2], [2, 2]]
+/// durations_ms = [80, 80, 200]
+/// events = { hit = [2] }
+/// boxes = [{ kind = "hitbox", frames = [2], rect = [6, 2, 4, 6] }]
+/// ```
+///
+/// Frame durations fall back to the clip's `speed_ms`, then to the set's `speed_ms`.
+/// Boxes are `[x, y, width, height]` in pixels from the top left of the frame, facing right.
+/// Frames without a hurtbox of their own use the set's `hurtbox`, or the whole frame
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnimationSet {
+    pub frame_size: (u16, u16),
+    #[serde(default)]
+    pub border: u16,
+    #[serde(default)]
+    pub spacing: u16,
+    #[serde(default)]
+    pub offset: (u16, u16),
+    /// Frame shown when no clip is playing
+    #[serde(default)]
+    pub default: (u16, u16),
+    pub speed_ms: u16,
+    pub hurtbox: Option<FrameRect>,
+    #[serde(default, rename = "clip")]
+    pub clips: Vec<Clip>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Clip {
+    pub name: String,
+    #[serde(default = "default_loop", rename = "loop")]
+    pub looping: bool,
+    pub frames: Vec<(u16, u16)>,
+    pub speed_ms: Option<u16>,
+    /// One duration per frame, overriding `speed_ms`
+    #[serde(default)]
+    pub durations_ms: Vec<u16>,
+    /// Event names, each with the indices of the frames that fire it
+    #[serde(default)]
+    pub events: BTreeMap<String, Vec<usize>>,
+    #[serde(default)]
+    pub boxes: Vec<ClipBox>,
+}
+
+/// Hitbox or hurtbox on some of a clip's frames
+#[derive(Clone, Debug, Deserialize)]
+pub struct ClipBox {
+    pub kind: ColliderKind,
+    /// Indices of the frames that have the box
+    pub frames: Vec<usize>,
+    pub rect: FrameRect,
+}
+
+fn default_loop() -> bool {
+    true
+}
+
+impl AnimationSet {
+    pub fn parse(text: &str) -> Result<AnimationSet, AnimationLoadError> {
+        let set: AnimationSet = toml::from_str(text)?;
+        if set.frame_size.0 == 0 || set.frame_size.1 == 0 {
+            return Err(AnimationLoadError::ZeroFrameSize(set.frame_size));
+        }
+        Ok(set)
+    }
+    pub fn layout(&self) -> SheetLayout {
+        SheetLayout {
+            frame_size: self.frame_size,
+            border: self.border,
+            spacing: self.spacing,
+            offset: self.offset,
+        }
+    }
+    /// Size in pixels of the smallest sheet that holds every frame the set uses
+    pub fn sheet_size(&self) -> (u32, u32) {
+        let layout = self.layout();
+        let stride = layout.stride();
+        let (columns, rows) = self
+            .clips
+            .iter()
+            .flat_map(|clip| clip.frames.iter())
+            .chain(std::iter::once(&self.default))
+            .fold((0, 0), |(columns, rows), frame| {
+                (columns.max(frame.0 as u32 + 1), rows.max(frame.1 as u32 + 1))
+            });
+        (
+            (layout.offset.0 as u32 + columns * stride.0).saturating_sub(layout.spacing as u32),
+            (layout.offset.1 as u32 + rows * stride.1).saturating_sub(layout.spacing as u32),
+        )
+    }
+    /// Checks every clip against a sheet of `sheet_size` pixels and collects them by name
+    pub fn into_frames(
+        self,
+        sheet_size: (u32, u32),
+    ) -> Result<HashMap<String, FrameLoop>, AnimationLoadError> {
+        let (columns, rows) = self.layout().frame_count(sheet_size);
+        let in_bounds = |frame: &(u16, u16)| (frame.0 as u32) < columns && (frame.1 as u32) < rows;
+
+        if !in_bounds(&self.default) {
+            return Err(AnimationLoadError::FrameOutOfBounds {
+                clip: String::from("default"),
+                frame: self.default,
+            });
+        }
+
+        let mut frames = HashMap::with_capacity(self.clips.len());
+        for clip in self.clips {
+            if frames.contains_key(&clip.name) {
+                return Err(AnimationLoadError::DuplicateClip(clip.name));
+            }
+            let frame_loop = clip.to_frame_loop(self.speed_ms, &in_bounds)?;
+            frames.insert(clip.name, frame_loop);
+        }
+        Ok(frames)
+    }
+}
+
+impl Clip {
+    fn to_frame_loop(
+        &self,
+        default_speed_ms: u16,
+        in_bounds: &impl Fn(&(u16, u16)) -> bool,
+    ) -> Result<FrameLoop, AnimationLoadError> {
+        if self.frames.is_empty() {
+            return Err(AnimationLoadError::EmptyClip(self.name.clone()));
+        }
+        if let Some(frame) = self.frames.iter().find(|f| !in_bounds(f)) {
+            return Err(AnimationLoadError::FrameOutOfBounds {
+                clip: self.name.clone(),
+                frame: *frame,
+            });
+        }
+        if !self.durations_ms.is_empty() && self.durations_ms.len() != self.frames.len() {
+            return Err(AnimationLoadError::DurationCount {
+                clip: self.name.clone(),
+                expected: self.frames.len(),
+                found: self.durations_ms.len(),
+            });
+        }
+
+        let speed_ms = self.speed_ms.unwrap_or(default_speed_ms);
+        let mut frames: Vec<Frame> = self
+            .frames
+            .iter()
+            .enumerate()
+            .map(|(i, pos)| Frame {
+                pos: *pos,
+                duration_ms: self.durations_ms.get(i).copied().unwrap_or(speed_ms),
+                events: Vec::new(),
+                boxes: Vec::new(),
+            })
+            .collect();
+        if frames.iter().all(|frame| frame.duration_ms == 0) {
+            return Err(AnimationLoadError::ZeroDuration(self.name.clone()));
+        }
+
+        for (event, indices) in &self.events {
+            for &index in indices {
+                match frames.get_mut(index) {
+                    Some(frame) => frame.events.push(event.clone()),
+                    None => {
+                        return Err(AnimationLoadError::EventOutOfBounds {
+                            clip: self.name.clone(),
+                            event: event.clone(),
+                            index,
+                        })
+                    }
+                }
+            }
+        }
+
+        for clip_box in &self.boxes {
+            for &index in &clip_box.frames {
+                match frames.get_mut(index) {
+                    Some(frame) => frame.boxes.push((clip_box.kind, clip_box.rect)),
+                    None => {
+                        return Err(AnimationLoadError::BoxOutOfBounds {
+                            clip: self.name.clone(),
+                            index,
+                        })
+                    }
+                }
+            }
+        }
+
+        Ok(FrameLoop {
+            looping: self.looping,
+            frames,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: (u32, u32) = (40, 20);
+
+    fn frames(text: &str) -> Result<HashMap<String, FrameLoop>, AnimationLoadError> {
+        AnimationSet::parse(text)?.into_frames(SHEET)
+    }
+
+    #[test]
+    fn clips_are_collected_by_name() {
+        let frames = frames(
+            r#"
+            frame_size = [10, 10]
+            speed_ms = 100
+
+            [[clip]]
+            name = "idle"
+            frames = [[0, 0], [1, 0]]
+
+            [[clip]]
+            name = "attack"
+            loop = false
+            frames = [[0, 1], [3, 1]]
+            durations_ms = [50, 200]
+            events = { hit = [1] }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(frames.len(), 2);
+        let attack = &frames["attack"];
+        assert!(!attack.looping);
+        assert_eq!(attack.frames[1].pos, (3, 1));
+        assert_eq!(attack.frames[1].duration_ms, 200);
+        assert_eq!(attack.frames[1].events, vec![String::from("hit")]);
+        assert_eq!(frames["idle"].frames[0].duration_ms, 100);
+    }
+
+    #[test]
+    fn duplicate_clips_are_refused() {
+        let result = frames(
+            r#"
+            frame_size = [10, 10]
+            speed_ms = 100
+
+            [[clip]]
+            name = "idle"
+            frames = [[0, 0]]
+
+            [[clip]]
+            name = "idle"
+            frames = [[1, 0]]
+            "#,
+        );
+        assert!(matches!(result, Err(AnimationLoadError::DuplicateClip(name)) if name == "idle"));
+    }
+
+    #[test]
+    fn frames_outside_the_sheet_are_refused() {
+        let result = frames(
+            r#"
+            frame_size = [10, 10]
+            speed_ms = 100
+
+            [[clip]]
+            name = "walk"
+            frames = [[0, 0], [4, 0]]
+            "#,
+        );
+        assert!(matches!(
+            result,
+            Err(AnimationLoadError::FrameOutOfBounds { clip, frame: (4, 0) }) if clip == "walk"
+        ));
+    }
+
+    #[test]
+    fn padding_counts_towards_the_sheet_bounds() {
+        // With a border, only three frames fit across the 40 pixel sheet
+        let result = frames(
+            r#"
+            frame_size = [10, 10]
+            border = 1
+            speed_ms = 100
+
+            [[clip]]
+            name = "walk"
+            frames = [[3, 0]]
+            "#,
+        );
+        assert!(matches!(result, Err(AnimationLoadError::FrameOutOfBounds { .. })));
+    }
+
+    #[test]
+    fn default_frame_outside_the_sheet_is_refused() {
+        let result = frames(
+            r#"
+            frame_size = [10, 10]
+            speed_ms = 100
+            default = [0, 2]
+            "#,
+        );
+        assert!(matches!(
+            result,
+            Err(AnimationLoadError::FrameOutOfBounds { clip, frame: (0, 2) }) if clip == "default"
+        ));
+    }
+
+    #[test]
+    fn frames_without_area_are_refused() {
+        let result = frames(
+            r#"
+            frame_size = [0, 0]
+            speed_ms = 100
+            "#,
+        );
+        assert!(matches!(result, Err(AnimationLoadError::ZeroFrameSize((0, 0)))));
+    }
+
+    #[test]
+    fn clips_that_take_no_time_are_refused() {
+        let result = frames(
+            r#"
+            frame_size = [10, 10]
+            speed_ms = 100
+
+            [[clip]]
+            name = "blink"
+            loop = false
+            frames = [[0, 0], [1, 0]]
+            durations_ms = [0, 0]
+            "#,
+        );
+        assert!(matches!(result, Err(AnimationLoadError::ZeroDuration(name)) if name == "blink"));
+    }
+}
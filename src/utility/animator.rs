@@ -0,0 +1,182 @@
+use speedy2d::{color::Color, shape::Rectangle, Graphics2D};
+
+use crate::{
+    entity::Facing,
+    error::GameError,
+    world::{
+        collision::{ColliderKind, EntityId, GameRect},
+        World,
+    },
+};
+
+use super::{
+    animation::{Animation, AnimationSelectError, Flip},
+    clock::Clock,
+};
+
+/// What the entity is doing, which the animator turns into a clip
+#[derive(Clone, Copy, Debug)]
+pub struct AnimParams {
+    pub moving: bool,
+    pub attacking: bool,
+    pub facing: Facing,
+}
+
+impl Default for AnimParams {
+    fn default() -> Self {
+        AnimParams {
+            moving: false,
+            attacking: false,
+            facing: Facing::Right,
+        }
+    }
+}
+
+/// A state the animator can be in
+///
+/// Among the states whose condition holds, the one with the highest priority plays.
+/// A state without a clip shows the sheet's default frame
+pub struct AnimState {
+    clip: Option<&'static str>,
+    priority: u8,
+    condition: fn(&AnimParams) -> bool,
+    interruptible: bool,
+    on_complete: Option<fn(&mut AnimParams)>,
+}
+
+impl AnimState {
+    pub fn new(
+        clip: Option<&'static str>,
+        priority: u8,
+        condition: fn(&AnimParams) -> bool,
+    ) -> AnimState {
+        AnimState {
+            clip,
+            priority,
+            condition,
+            interruptible: true,
+            on_complete: None,
+        }
+    }
+    /// Once entered, the state keeps playing until its clip finishes
+    ///
+    /// Only makes sense for clips that don't loop
+    pub fn uninterruptible(mut self) -> AnimState {
+        self.interruptible = false;
+        self
+    }
+    /// Called with the animator's parameters when the state's clip finishes
+    pub fn on_complete(mut self, callback: fn(&mut AnimParams)) -> AnimState {
+        self.on_complete = Some(callback);
+        self
+    }
+}
+
+/// Picks which clip of an `Animation` plays from a set of states and the entity's parameters
+pub struct Animator {
+    anim: Animation,
+    states: Vec<AnimState>,
+    current: Option<usize>,
+    pub params: AnimParams,
+}
+
+impl Animator {
+    /// Fails if any state refers to a clip the animation doesn't have
+    pub fn new(anim: Animation, states: Vec<AnimState>) -> Result<Animator, GameError> {
+        for state in &states {
+            if let Some(clip) = state.clip {
+                if !anim.has_clip(clip) {
+                    return Err(GameError::MissingClip(clip.to_string()));
+                }
+            }
+        }
+        Ok(Animator {
+            anim,
+            states,
+            current: None,
+            params: AnimParams::default(),
+        })
+    }
+    /// Moves to the state the parameters call for, then advances its clip
+    pub fn update(&mut self, clock: &Clock) {
+        let next = self.pick_state();
+        let clip = next.and_then(|i| self.states[i].clip);
+
+        if self.current != next {
+            match clip {
+                Some(clip) => {
+                    // `new` made sure every clip exists, and states sharing a clip just carry on with it
+                    let selected = self.anim.select(clip, clock);
+                    debug_assert!(
+                        !matches!(selected, Err(AnimationSelectError::NotFound)),
+                        "clip \"{}\" went missing after the animator was built",
+                        clip
+                    );
+                }
+                None => self.anim.deselect(),
+            }
+        }
+        self.current = next;
+
+        self.anim.update(clock);
+
+        if let Some(i) = next {
+            if clip.is_some() && self.anim.frame_loop.is_none() {
+                if let Some(callback) = self.states[i].on_complete {
+                    callback(&mut self.params);
+                }
+                self.current = None;
+            }
+        }
+    }
+    /// Events fired by the clip during the last update
+    pub fn events(&self) -> &[String] {
+        self.anim.events()
+    }
+    pub fn draw(&self, graphics: &mut Graphics2D, window_rect: Rectangle<f32>, color: Color) {
+        let flip = match self.params.facing {
+            Facing::Left => Flip::HORIZONTAL,
+            Facing::Right => Flip::NONE,
+        };
+        self.anim.draw(graphics, window_rect, color, flip);
+    }
+    /// Adds the current frame's hitboxes and hurtboxes to the world as `owner`'s,
+    /// with the frame stretched over `bounds` and mirrored when facing left
+    pub fn add_colliders(&self, owner: EntityId, bounds: GameRect, world: &mut World) {
+        let frame_size = self.anim.frame_size();
+        let scale = (
+            bounds.width() / frame_size.0 as f32,
+            bounds.height() / frame_size.1 as f32,
+        );
+        for kind in [ColliderKind::Hurtbox, ColliderKind::Hitbox] {
+            for (x, y, width, height) in self.anim.boxes(kind) {
+                let x = match self.params.facing {
+                    Facing::Right => x,
+                    Facing::Left => frame_size.0.saturating_sub(x + width),
+                };
+                let rect = GameRect::new(
+                    (
+                        bounds.min.x + x as f32 * scale.0,
+                        bounds.min.y + y as f32 * scale.1,
+                    )
+                        .into(),
+                    (width as f32 * scale.0, height as f32 * scale.1),
+                );
+                world.add_collider(owner, kind, rect);
+            }
+        }
+    }
+    fn pick_state(&self) -> Option<usize> {
+        if let Some(current) = self.current {
+            if !self.states[current].interruptible {
+                return Some(current);
+            }
+        }
+        self.states
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| (s.condition)(&self.params))
+            .max_by_key(|(_, s)| s.priority)
+            .map(|(i, _)| i)
+    }
+}
@@ -0,0 +1,137 @@
+use std::{
+    borrow::Cow,
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use super::assets::AssetError;
+
+mod embedded {
+    // Generated by build.rs, empty unless the `embed-assets` feature is on
+    include!(concat!(env!("OUT_DIR"), "/embedded_assets.rs"));
+}
+
+/// Environment variable that points the game at an assets folder
+pub const ASSET_ROOT_VAR: &str = "GOBLINS_ASSETS";
+
+static ROOTS: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+/// Makes `root` the only folder assets are read from
+///
+/// Has no effect once the first asset has been loaded
+pub fn set_root(root: PathBuf) {
+    let _ = ROOTS.set(vec![root]);
+}
+
+/// Folders searched for assets, in order
+fn roots() -> &'static [PathBuf] {
+    ROOTS.get_or_init(search_roots)
+}
+
+/// Either `GOBLINS_ASSETS`, or the `assets` folder next to the executable if there is one,
+/// then `assets` in the working directory
+fn search_roots() -> Vec<PathBuf> {
+    search_roots_from(env::var_os(ASSET_ROOT_VAR))
+}
+
+/// Like `search_roots`, with `env_value` standing in for `GOBLINS_ASSETS`
+fn search_roots_from(env_value: Option<OsString>) -> Vec<PathBuf> {
+    if let Some(root) = env_value {
+        return vec![PathBuf::from(root)];
+    }
+    let mut roots = Vec::new();
+    if let Ok(exe) = env::current_exe() {
+        if let Some(dir) = exe.parent() {
+            let candidate = dir.join("assets");
+            if candidate.is_dir() {
+                roots.push(candidate);
+            }
+        }
+    }
+    let working = PathBuf::from("assets");
+    let working_full = working.canonicalize().ok();
+    if !roots.iter().any(|root| root.canonicalize().ok() == working_full) {
+        roots.push(working);
+    }
+    roots
+}
+
+/// Reads the asset at `path`, given relative to the assets folder with `/` separators,
+/// e.g. `img/goblin.png`
+///
+/// Files on disk take priority over embedded ones, so a build with embedded assets can still be modded
+pub fn read(path: &str) -> Result<Cow<'static, [u8]>, AssetError> {
+    let relative: PathBuf = path.split('/').collect();
+    for root in roots() {
+        let full = root.join(&relative);
+        if full.is_file() {
+            return fs::read(&full)
+                .map(Cow::Owned)
+                .map_err(|error| AssetError::Io {
+                    path: full.display().to_string(),
+                    error,
+                });
+        }
+    }
+    if let Some((_, bytes)) = embedded::ASSETS.iter().find(|(name, _)| *name == path) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    Err(AssetError::Missing {
+        path: path.to_string(),
+        searched: roots().iter().map(|r| display_root(r)).collect(),
+    })
+}
+
+pub fn read_to_string(path: &str) -> Result<String, AssetError> {
+    let bytes = read(path)?;
+    String::from_utf8(bytes.into_owned()).map_err(|_| AssetError::Encoding {
+        path: path.to_string(),
+    })
+}
+
+/// Writes `bytes` to the asset at `path` in the first folder searched,
+/// so it's the file read back from then on, and returns where that is
+pub fn write(path: &str, bytes: &[u8]) -> Result<String, AssetError> {
+    let relative: PathBuf = path.split('/').collect();
+    // There's always at least the working directory's folder to search
+    let full = roots()[0].join(relative);
+    let error = |error| AssetError::Write {
+        path: full.display().to_string(),
+        error,
+    };
+    if let Some(parent) = full.parent() {
+        fs::create_dir_all(parent).map_err(error)?;
+    }
+    fs::write(&full, bytes).map_err(error)?;
+    Ok(full.display().to_string())
+}
+
+fn display_root(root: &Path) -> String {
+    match root.canonicalize() {
+        Ok(full) => full.display().to_string(),
+        Err(_) => root.display().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_roots_follows_the_environment() {
+        let root = env::temp_dir().join("goblins-assets-test");
+        assert_eq!(
+            search_roots_from(Some(root.clone().into_os_string())),
+            vec![root]
+        );
+
+        // Without it, only the executable's folder and the working directory are searched
+        let exe_dir = env::current_exe().unwrap().parent().unwrap().to_path_buf();
+        for root in search_roots_from(None) {
+            assert!(root == exe_dir.join("assets") || root == Path::new("assets"));
+        }
+    }
+}
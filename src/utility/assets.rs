@@ -0,0 +1,280 @@
+use std::{cell::RefCell, collections::HashMap, fmt, io, io::Cursor};
+
+use serde::{de::DeserializeOwned, Serialize};
+use speedy2d::{
+    error::{BacktraceError, ErrorMessage},
+    font::Font,
+    image::{ImageDataType, ImageFileFormat, ImageHandle, ImageSmoothingMode},
+    Graphics2D,
+};
+
+use super::{
+    animation_set::{AnimationLoadError, AnimationSet},
+    asset_path,
+    sheet::{self, Rgb},
+};
+
+#[derive(Debug)]
+pub enum AssetError {
+    Missing { path: String, searched: Vec<String> },
+    Io { path: String, error: io::Error },
+    Encoding { path: String },
+    Image { path: String, error: BacktraceError<ErrorMessage> },
+    Decode { path: String, error: image::ImageError },
+    Font { path: String, error: BacktraceError<ErrorMessage> },
+    Animation { path: String, error: AnimationLoadError },
+    Data { path: String, error: toml::de::Error },
+    Write { path: String, error: io::Error },
+    Encode { path: String, error: toml::ser::Error },
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssetError::Missing { path, searched } => write!(
+                f,
+                "asset {} not found, looked in: {} (point {} or --assets at the assets folder)",
+                path,
+                searched.join(", "),
+                asset_path::ASSET_ROOT_VAR
+            ),
+            AssetError::Io { path, error } => write!(f, "could not read {}: {}", path, error),
+            AssetError::Encoding { path } => write!(f, "{} is not valid UTF-8", path),
+            AssetError::Image { path, error } => write!(f, "could not load image {}: {}", path, error),
+            AssetError::Decode { path, error } => write!(f, "could not decode image {}: {}", path, error),
+            AssetError::Font { path, error } => write!(f, "could not load font {}: {}", path, error),
+            AssetError::Animation { path, error } => write!(f, "{}: {}", path, error),
+            AssetError::Data { path, error } => write!(f, "invalid data in {}: {}", path, error),
+            AssetError::Write { path, error } => write!(f, "could not write {}: {}", path, error),
+            AssetError::Encode { path, error } => write!(f, "could not save {} as TOML: {}", path, error),
+        }
+    }
+}
+
+/// How many times each kind of asset was actually loaded, and how many times the cache served it
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AssetStats {
+    pub image_loads: u32,
+    pub image_hits: u32,
+    pub font_loads: u32,
+    pub font_hits: u32,
+    pub animation_loads: u32,
+    pub animation_hits: u32,
+}
+
+impl fmt::Display for AssetStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "images {} loaded / {} cached, fonts {} / {}, animations {} / {}",
+            self.image_loads,
+            self.image_hits,
+            self.font_loads,
+            self.font_hits,
+            self.animation_loads,
+            self.animation_hits
+        )
+    }
+}
+
+#[derive(Default)]
+struct AssetCache {
+    images: HashMap<String, ImageHandle>,
+    fonts: HashMap<String, Font>,
+    animations: HashMap<String, AnimationSet>,
+    stats: AssetStats,
+}
+
+thread_local! {
+    // Image handles belong to the window's graphics context, which lives on the main thread
+    static ASSETS: RefCell<AssetCache> = RefCell::new(AssetCache::default());
+}
+
+/// Loads the image at `path` the first time it's asked for, and hands out the same texture after that
+///
+/// Like every asset path, `path` is relative to the assets folder, e.g. `img/goblin.png`
+pub fn load_image(graphics: &mut Graphics2D, path: &str) -> Result<ImageHandle, AssetError> {
+    ASSETS.with(|assets| {
+        let mut assets = assets.borrow_mut();
+        if let Some(image) = assets.images.get(path).cloned() {
+            assets.stats.image_hits += 1;
+            return Ok(image);
+        }
+        let bytes = asset_path::read(path)?;
+        let image = graphics
+            .create_image_from_file_bytes(
+                Some(ImageFileFormat::PNG),
+                ImageSmoothingMode::NearestNeighbor,
+                Cursor::new(bytes),
+            )
+            .map_err(|error| AssetError::Image {
+                path: path.to_string(),
+                error,
+            })?;
+        assets.stats.image_loads += 1;
+        assets.images.insert(path.to_string(), image.clone());
+        Ok(image)
+    })
+}
+
+/// Loads the image at `path` with its colours swapped according to `palette`,
+/// so one sprite sheet can be drawn in several colour schemes
+///
+/// Each distinct palette is its own texture, cached like any other image
+pub fn load_image_swapped(
+    graphics: &mut Graphics2D,
+    path: &str,
+    palette: &[(Rgb, Rgb)],
+) -> Result<ImageHandle, AssetError> {
+    if palette.is_empty() {
+        return load_image(graphics, path);
+    }
+    let key = palette_key(path, palette);
+    ASSETS.with(|assets| {
+        let mut assets = assets.borrow_mut();
+        if let Some(image) = assets.images.get(&key).cloned() {
+            assets.stats.image_hits += 1;
+            return Ok(image);
+        }
+        let bytes = asset_path::read(path)?;
+        let mut pixels = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png)
+            .map_err(|error| AssetError::Decode {
+                path: path.to_string(),
+                error,
+            })?
+            .to_rgba8();
+        sheet::swap_palette(&mut pixels, palette);
+        let image = graphics
+            .create_image_from_raw_pixels(
+                ImageDataType::RGBA,
+                ImageSmoothingMode::NearestNeighbor,
+                pixels.dimensions(),
+                pixels.as_raw(),
+            )
+            .map_err(|error| AssetError::Image {
+                path: path.to_string(),
+                error,
+            })?;
+        assets.stats.image_loads += 1;
+        assets.images.insert(key, image.clone());
+        Ok(image)
+    })
+}
+
+/// Loads the image at `path` like `load_image_swapped`, or if that fails,
+/// reports why and hands out a checkerboard of `size` pixels in its place from then on
+pub fn load_image_or_missing(
+    graphics: &mut Graphics2D,
+    path: &str,
+    palette: &[(Rgb, Rgb)],
+    size: (u32, u32),
+) -> Result<ImageHandle, AssetError> {
+    match load_image_swapped(graphics, path, palette) {
+        Ok(image) => Ok(image),
+        Err(error) => {
+            eprintln!("{}, drawing a placeholder instead", error);
+            let image = missing_image(graphics, size).map_err(|error| AssetError::Image {
+                path: path.to_string(),
+                error,
+            })?;
+            ASSETS.with(|assets| {
+                assets.borrow_mut().images.insert(palette_key(path, palette), image.clone());
+            });
+            Ok(image)
+        }
+    }
+}
+
+/// Cache key for the image at `path` recoloured with `palette`, which is just `path` without one
+fn palette_key(path: &str, palette: &[(Rgb, Rgb)]) -> String {
+    let mut key = path.to_string();
+    for (from, to) in palette {
+        key.push_str(&format!(" {}>{}", from, to));
+    }
+    key
+}
+
+/// Magenta and black checkerboard, which is hard to mistake for a real sprite
+fn missing_image(
+    graphics: &mut Graphics2D,
+    size: (u32, u32),
+) -> Result<ImageHandle, BacktraceError<ErrorMessage>> {
+    const CELL: u32 = 2;
+    let size = (size.0.max(1), size.1.max(1));
+    let mut pixels = Vec::with_capacity((size.0 * size.1 * 4) as usize);
+    for y in 0..size.1 {
+        for x in 0..size.0 {
+            if (x / CELL + y / CELL) % 2 == 0 {
+                pixels.extend_from_slice(&[255, 0, 255, 255]);
+            } else {
+                pixels.extend_from_slice(&[0, 0, 0, 255]);
+            }
+        }
+    }
+    graphics.create_image_from_raw_pixels(
+        ImageDataType::RGBA,
+        ImageSmoothingMode::NearestNeighbor,
+        size,
+        &pixels,
+    )
+}
+
+pub fn load_font(path: &str) -> Result<Font, AssetError> {
+    ASSETS.with(|assets| {
+        let mut assets = assets.borrow_mut();
+        if let Some(font) = assets.fonts.get(path).cloned() {
+            assets.stats.font_hits += 1;
+            return Ok(font);
+        }
+        let bytes = asset_path::read(path)?;
+        let font = Font::new(&bytes).map_err(|error| AssetError::Font {
+            path: path.to_string(),
+            error,
+        })?;
+        assets.stats.font_loads += 1;
+        assets.fonts.insert(path.to_string(), font.clone());
+        Ok(font)
+    })
+}
+
+pub fn load_animation_set(path: &str) -> Result<AnimationSet, AssetError> {
+    ASSETS.with(|assets| {
+        let mut assets = assets.borrow_mut();
+        if let Some(set) = assets.animations.get(path).cloned() {
+            assets.stats.animation_hits += 1;
+            return Ok(set);
+        }
+        let text = asset_path::read_to_string(path)?;
+        let set = AnimationSet::parse(&text).map_err(|error| AssetError::Animation {
+            path: path.to_string(),
+            error,
+        })?;
+        assets.stats.animation_loads += 1;
+        assets.animations.insert(path.to_string(), set.clone());
+        Ok(set)
+    })
+}
+
+/// Reads a TOML data file at `path` into `T`
+///
+/// Unlike the other assets, data isn't cached, so only read it once when setting things up
+pub fn load_data<T: DeserializeOwned>(path: &str) -> Result<T, AssetError> {
+    let text = asset_path::read_to_string(path)?;
+    toml::from_str(&text).map_err(|error| AssetError::Data {
+        path: path.to_string(),
+        error,
+    })
+}
+
+/// Writes `data` to a TOML file at `path`, returning where on disk it went
+pub fn save_data<T: Serialize>(path: &str, data: &T) -> Result<String, AssetError> {
+    let text = toml::to_string_pretty(data).map_err(|error| AssetError::Encode {
+        path: path.to_string(),
+        error,
+    })?;
+    asset_path::write(path, text.as_bytes())
+}
+
+pub fn stats() -> AssetStats {
+    ASSETS.with(|assets| assets.borrow().stats)
+}
@@ -0,0 +1,101 @@
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ClockMode {
+    /// Follows the wall clock
+    Real,
+    /// Time stands still
+    Paused,
+    /// Follows the wall clock at the given rate, e.g. 0.5 for half speed
+    Scaled(f32),
+    /// Only moves when `step` is called
+    Manual,
+}
+
+/// Game time, which everything that animates or waits should read instead of `Instant::now()`
+///
+/// Game time only moves forward when the clock is ticked (or stepped),
+/// so it can be paused, slowed down or driven by hand
+pub struct Clock {
+    mode: ClockMode,
+    now: Duration,
+    last_tick: Instant,
+}
+
+impl Clock {
+    pub fn new(mode: ClockMode) -> Clock {
+        Clock {
+            mode,
+            now: Duration::ZERO,
+            last_tick: Instant::now(),
+        }
+    }
+    /// Advances game time by however much real time has passed since the last tick,
+    /// according to the mode, and returns how far it moved
+    pub fn tick(&mut self) -> Duration {
+        let real_now = Instant::now();
+        let real_dt = real_now - self.last_tick;
+        self.last_tick = real_now;
+
+        let dt = match self.mode {
+            ClockMode::Real => real_dt,
+            ClockMode::Paused | ClockMode::Manual => Duration::ZERO,
+            ClockMode::Scaled(rate) => real_dt.mul_f32(rate.max(0.0)),
+        };
+        self.now += dt;
+        dt
+    }
+    /// Advances game time by `dt` whatever the mode
+    pub fn step(&mut self, dt: Duration) {
+        self.now += dt;
+    }
+    /// Game time since the clock was created
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+    pub fn mode(&self) -> ClockMode {
+        self.mode
+    }
+    pub fn set_mode(&mut self, mode: ClockMode) {
+        // Real time spent in the old mode shouldn't count towards the new one
+        self.last_tick = Instant::now();
+        self.mode = mode;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manual_clocks_only_move_when_stepped() {
+        let mut clock = Clock::new(ClockMode::Manual);
+        assert_eq!(clock.tick(), Duration::ZERO);
+        clock.step(Duration::from_millis(16));
+        clock.step(Duration::from_millis(16));
+        assert_eq!(clock.now(), Duration::from_millis(32));
+    }
+
+    #[test]
+    fn paused_clocks_stand_still() {
+        let mut clock = Clock::new(ClockMode::Paused);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.tick(), Duration::ZERO);
+        assert_eq!(clock.now(), Duration::ZERO);
+    }
+
+    #[test]
+    fn scaled_clocks_run_slower_than_real_time() {
+        let mut clock = Clock::new(ClockMode::Scaled(0.0));
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.tick(), Duration::ZERO);
+
+        clock.set_mode(ClockMode::Scaled(0.5));
+        assert_eq!(clock.mode(), ClockMode::Scaled(0.5));
+        let start = Instant::now();
+        std::thread::sleep(Duration::from_millis(20));
+        let dt = clock.tick();
+        assert!(dt >= Duration::from_millis(10));
+        assert!(dt <= start.elapsed() / 2 + Duration::from_millis(1));
+    }
+}
@@ -1,2 +1,8 @@
 pub mod animation;
-pub mod serial_namer;
\ No newline at end of file
+pub mod animation_set;
+pub mod animator;
+pub mod asset_path;
+pub mod assets;
+pub mod clock;
+pub mod serial_namer;
+pub mod sheet;
\ No newline at end of file
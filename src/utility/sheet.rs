@@ -0,0 +1,162 @@
+use std::{convert::TryFrom, fmt};
+
+use image::{ImageResult, RgbaImage};
+use serde::{Deserialize, Serialize};
+use speedy2d::color::Color;
+
+use super::animation::SheetLayout;
+
+/// Re-exports a sprite sheet so that every frame is surrounded by `border` pixels
+/// copied from the frame's own edges
+///
+/// Without this, scaling the sheet lets neighbouring frames bleed into each other
+pub fn extrude(src: &RgbaImage, from: SheetLayout, border: u16) -> (RgbaImage, SheetLayout) {
+    let to = SheetLayout::new(from.frame_size).with_border(border);
+
+    let (columns, rows) = from.frame_count(src.dimensions());
+    let stride = to.stride();
+    let mut out = RgbaImage::new(columns * stride.0, rows * stride.1);
+
+    let frame_w = from.frame_size.0 as i64;
+    let frame_h = from.frame_size.1 as i64;
+    let border = border as i64;
+
+    for row in 0..rows {
+        for column in 0..columns {
+            let src_origin = from.frame_origin((column as u16, row as u16));
+            let dst_origin = to.frame_origin((column as u16, row as u16));
+
+            for y in -border..frame_h + border {
+                for x in -border..frame_w + border {
+                    // Pixels in the border take the colour of the closest pixel inside the frame
+                    let sx = src_origin.0 + x.clamp(0, frame_w - 1) as u32;
+                    let sy = src_origin.1 + y.clamp(0, frame_h - 1) as u32;
+                    let dx = (dst_origin.0 as i64 + x) as u32;
+                    let dy = (dst_origin.1 as i64 + y) as u32;
+                    out.put_pixel(dx, dy, *src.get_pixel(sx, sy));
+                }
+            }
+        }
+    }
+
+    (out, to)
+}
+
+/// Loads the sheet at `input`, extrudes it and saves the result to `output`
+pub fn extrude_file(input: &str, output: &str, from: SheetLayout, border: u16) -> ImageResult<SheetLayout> {
+    let src = image::open(input)?.to_rgba8();
+    let (out, layout) = extrude(&src, from, border);
+    out.save(output)?;
+    Ok(layout)
+}
+
+/// Colour without alpha, written as a hex string like `"#00a860"` in data files
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Rgb(pub [u8; 3]);
+
+impl TryFrom<String> for Rgb {
+    type Error = String;
+
+    fn try_from(hex: String) -> Result<Self, Self::Error> {
+        let digits = hex.strip_prefix('#').unwrap_or(&hex);
+        let channel = |i: usize| {
+            digits
+                .get(i..i + 2)
+                .and_then(|d| u8::from_str_radix(d, 16).ok())
+                .ok_or_else(|| format!("{} is not a colour like #00a860", hex))
+        };
+        if digits.len() != 6 {
+            return Err(format!("{} is not a colour like #00a860", hex));
+        }
+        Ok(Rgb([channel(0)?, channel(2)?, channel(4)?]))
+    }
+}
+
+impl From<Rgb> for String {
+    fn from(rgb: Rgb) -> Self {
+        rgb.to_string()
+    }
+}
+
+impl Rgb {
+    pub fn to_color(self) -> Color {
+        Color::from_int_rgb(self.0[0], self.0[1], self.0[2])
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0[0], self.0[1], self.0[2])
+    }
+}
+
+/// Recolours every pixel matching the first colour of a pair to the second, keeping its alpha
+pub fn swap_palette(image: &mut RgbaImage, palette: &[(Rgb, Rgb)]) {
+    for pixel in image.pixels_mut() {
+        let rgb = [pixel[0], pixel[1], pixel[2]];
+        if let Some((_, to)) = palette.iter().find(|(from, _)| from.0 == rgb) {
+            pixel[0] = to.0[0];
+            pixel[1] = to.0[1];
+            pixel[2] = to.0[2];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    /// A sheet of two 2x2 frames side by side, every pixel a different colour
+    fn two_frames() -> RgbaImage {
+        RgbaImage::from_fn(4, 2, |x, y| Rgba([x as u8 * 10, y as u8 * 10, 0, 255]))
+    }
+
+    #[test]
+    fn extrude_pads_every_frame() {
+        let (out, layout) = extrude(&two_frames(), SheetLayout::new((2, 2)), 1);
+        assert_eq!(layout, SheetLayout::new((2, 2)).with_border(1));
+        assert_eq!(out.dimensions(), (8, 4));
+    }
+
+    #[test]
+    fn extrude_keeps_frames_and_copies_their_edges() {
+        let src = two_frames();
+        let (out, layout) = extrude(&src, SheetLayout::new((2, 2)), 1);
+        for column in 0..2u32 {
+            let origin = layout.frame_origin((column as u16, 0));
+            for y in 0..2 {
+                for x in 0..2 {
+                    assert_eq!(
+                        out.get_pixel(origin.0 + x, origin.1 + y),
+                        src.get_pixel(column * 2 + x, y)
+                    );
+                }
+            }
+            // Corners of the border copy the frame's corners
+            assert_eq!(
+                out.get_pixel(origin.0 - 1, origin.1 - 1),
+                src.get_pixel(column * 2, 0)
+            );
+            assert_eq!(
+                out.get_pixel(origin.0 + 2, origin.1 + 2),
+                src.get_pixel(column * 2 + 1, 1)
+            );
+            // Sides copy the edge next to them
+            assert_eq!(
+                out.get_pixel(origin.0 + 2, origin.1),
+                src.get_pixel(column * 2 + 1, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn extrude_reads_padded_sheets() {
+        let (padded, from) = extrude(&two_frames(), SheetLayout::new((2, 2)), 1);
+        let (out, _) = extrude(&padded, from, 2);
+        let (again, _) = extrude(&two_frames(), SheetLayout::new((2, 2)), 2);
+        assert_eq!(out, again);
+    }
+}
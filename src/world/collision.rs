@@ -0,0 +1,62 @@
+use serde::Deserialize;
+
+use super::space::GamePos;
+
+/// Axis-aligned rectangle in game units
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GameRect {
+    pub min: GamePos,
+    pub max: GamePos,
+}
+
+impl GameRect {
+    pub fn new(pos: GamePos, size: (f32, f32)) -> GameRect {
+        GameRect {
+            min: pos,
+            max: (pos.x + size.0, pos.y + size.1).into(),
+        }
+    }
+    /// Touching edges don't count as overlapping
+    pub fn overlaps(&self, other: &GameRect) -> bool {
+        self.min.x < other.max.x
+            && other.min.x < self.max.x
+            && self.min.y < other.max.y
+            && other.min.y < self.max.y
+    }
+    pub fn center(&self) -> GamePos {
+        (self.min + self.max) / 2.0
+    }
+    pub fn width(&self) -> f32 {
+        self.max.x - self.min.x
+    }
+    pub fn height(&self) -> f32 {
+        self.max.y - self.min.y
+    }
+}
+
+/// Identifies an entity in the world, and never gets reused
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityId(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColliderKind {
+    /// Where the entity can be hit
+    Hurtbox,
+    /// Where the entity's attack lands
+    Hitbox,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Collider {
+    pub owner: EntityId,
+    pub kind: ColliderKind,
+    pub rect: GameRect,
+}
+
+/// One entity's hitbox touching another's hurtbox
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collision {
+    pub attacker: EntityId,
+    pub target: EntityId,
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+/// Kills per minute a player keeping up with the game makes
+const PAR_KILLS_PER_MINUTE: f32 = 12.0;
+
+/// Damage per minute a player keeping up with the game takes
+const PAR_DAMAGE_PER_MINUTE: f32 = 4.0;
+
+/// Performance is judged over at least this long, so the first few kills or hits don't swing it
+const MIN_JUDGED: Duration = Duration::from_secs(60);
+
+/// Intensity never leaves this range
+const MIN_INTENSITY: f32 = 0.5;
+const MAX_INTENSITY: f32 = 4.0;
+
+/// How hard a run starts out and how quickly it gets harder
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Preset {
+    Easy,
+    Normal,
+    Hard,
+}
+
+/// Tuning behind a preset
+#[derive(Clone, Copy, Debug)]
+pub struct Curve {
+    /// Intensity at the start of a run
+    pub base: f32,
+    /// Intensity gained each minute
+    pub per_minute: f32,
+    /// Intensity gained each wave after the first
+    pub per_wave: f32,
+    /// How far a player doing well or badly pushes intensity up or down, as a fraction
+    pub adapt: f32,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Easy, Preset::Normal, Preset::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Preset::Easy => "Easy",
+            Preset::Normal => "Normal",
+            Preset::Hard => "Hard",
+        }
+    }
+    /// Easy leans harder on helping a struggling player, Hard barely lets up
+    pub fn curve(self) -> Curve {
+        match self {
+            Preset::Easy => Curve {
+                base: 0.7,
+                per_minute: 0.04,
+                per_wave: 0.05,
+                adapt: 0.3,
+            },
+            Preset::Normal => Curve {
+                base: 1.0,
+                per_minute: 0.06,
+                per_wave: 0.1,
+                adapt: 0.2,
+            },
+            Preset::Hard => Curve {
+                base: 1.3,
+                per_minute: 0.1,
+                per_wave: 0.15,
+                adapt: 0.1,
+            },
+        }
+    }
+}
+
+/// How much tougher goblins are than the numbers in their kind
+#[derive(Clone, Copy, Debug)]
+pub struct Scaling {
+    pub health: f32,
+    pub damage: f32,
+    pub speed: f32,
+}
+
+/// Keeps track of how a run is going and how hard it should be because of it
+///
+/// Everything comes down to one intensity, where 1 is the game as the data files describe it
+pub struct Difficulty {
+    pub preset: Preset,
+    elapsed: Duration,
+    wave: u32,
+    kills: u32,
+    damage_taken: u32,
+}
+
+impl Difficulty {
+    pub fn new(preset: Preset) -> Difficulty {
+        Difficulty {
+            preset,
+            elapsed: Duration::ZERO,
+            wave: 1,
+            kills: 0,
+            damage_taken: 0,
+        }
+    }
+    pub fn update(&mut self, dt: Duration, wave: u32) {
+        self.elapsed += dt;
+        self.wave = wave.max(1);
+    }
+    pub fn add_kills(&mut self, kills: u32) {
+        self.kills += kills;
+    }
+    pub fn add_damage_taken(&mut self, damage: u32) {
+        self.damage_taken += damage;
+    }
+    /// From -1 for a player being overwhelmed to 1 for one tearing through everything
+    fn performance(&self) -> f32 {
+        let minutes = self.elapsed.max(MIN_JUDGED).as_secs_f32() / 60.0;
+        let kills = self.kills as f32 / minutes / PAR_KILLS_PER_MINUTE;
+        let damage = self.damage_taken as f32 / minutes / PAR_DAMAGE_PER_MINUTE;
+        (kills - damage).clamp(-1.0, 1.0)
+    }
+    pub fn intensity(&self) -> f32 {
+        let curve = self.preset.curve();
+        let minutes = self.elapsed.as_secs_f32() / 60.0;
+        let ramp = curve.base + curve.per_minute * minutes + curve.per_wave * (self.wave - 1) as f32;
+        (ramp * (1.0 + curve.adapt * self.performance())).clamp(MIN_INTENSITY, MAX_INTENSITY)
+    }
+    /// How much faster spawns come than the waves file says
+    pub fn spawn_rate(&self) -> f32 {
+        self.intensity()
+    }
+    /// Health grows with intensity, while damage and speed grow slower so hits stay fair and goblins stay catchable
+    pub fn scaling(&self) -> Scaling {
+        let intensity = self.intensity();
+        Scaling {
+            health: intensity,
+            damage: intensity.sqrt(),
+            speed: intensity.powf(0.25),
+        }
+    }
+    /// How much more often a goblin of `threat` spawns than its weight says, when a wave lets chance pick
+    ///
+    /// Threat 1 goblins keep their weight, and tougher ones get more common as intensity rises
+    pub fn mix(&self, threat: u32) -> f32 {
+        self.intensity().powi(threat as i32 - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn intensity_stays_in_range() {
+        let mut easy = Difficulty::new(Preset::Easy);
+        easy.add_damage_taken(1_000);
+        assert_eq!(easy.intensity(), MIN_INTENSITY);
+
+        let mut hard = Difficulty::new(Preset::Hard);
+        hard.update(MINUTE * 120, 50);
+        hard.add_kills(100_000);
+        assert_eq!(hard.intensity(), MAX_INTENSITY);
+    }
+
+    #[test]
+    fn intensity_starts_at_the_presets_base() {
+        for preset in Preset::ALL.iter().copied() {
+            assert_eq!(Difficulty::new(preset).intensity(), preset.curve().base);
+        }
+    }
+
+    #[test]
+    fn performance_is_judged_over_at_least_a_minute() {
+        // A kill a second into the run counts as one kill in a minute, not sixty
+        let mut early = Difficulty::new(Preset::Normal);
+        early.update(Duration::from_secs(1), 1);
+        early.add_kills(1);
+        assert_eq!(early.performance(), 1.0 / PAR_KILLS_PER_MINUTE);
+
+        let mut late = Difficulty::new(Preset::Normal);
+        late.update(MINUTE * 2, 1);
+        late.add_kills(12);
+        late.add_damage_taken(4);
+        assert_eq!(late.performance(), 0.5 - 0.5);
+
+        late.add_kills(12);
+        assert_eq!(late.performance(), 1.0 - 0.5);
+    }
+
+    #[test]
+    fn performance_is_capped() {
+        let mut difficulty = Difficulty::new(Preset::Normal);
+        difficulty.add_kills(1_000);
+        assert_eq!(difficulty.performance(), 1.0);
+        difficulty.add_damage_taken(10_000);
+        assert_eq!(difficulty.performance(), -1.0);
+    }
+}
@@ -0,0 +1,323 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Where waves are defined
+pub const WAVES_PATH: &str = "data/waves.toml";
+
+/// Where a goblin comes from
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpawnPoint {
+    /// Just past the left edge of the screen
+    Left,
+    /// Just past the right edge of the screen
+    Right,
+    /// Past whichever edge of the screen chance picks
+    #[default]
+    Either,
+    /// A fixed spot in the world, at this many units along
+    At(f32),
+    /// The level's spawn marker with this name
+    Marker(String),
+}
+
+/// A run of goblins of one kind within a wave
+#[derive(Clone, Debug, Deserialize)]
+pub struct Group {
+    /// Name of a goblin kind, or any kind picked by weight if left out
+    pub kind: Option<String>,
+    pub count: u32,
+    /// Time after the wave starts before the first one spawns
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Time between each one spawning
+    #[serde(default)]
+    pub interval_ms: u64,
+    #[serde(default)]
+    pub spawn: SpawnPoint,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Wave {
+    #[serde(rename = "group")]
+    pub groups: Vec<Group>,
+}
+
+/// How a run is paced, from the waves file
+///
+/// ```toml
+/// max_alive = 12
+/// intermission_ms = 5000
+///
+/// [[wave]]
+/// [[wave.group]]
+/// kind = "grunt"
+/// count = 4
+/// interval_ms = 1000
+/// spawn = "right"
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+pub struct Waves {
+    /// Spawning holds off while this many goblins are alive
+    pub max_alive: usize,
+    /// Break before each wave, including the first
+    pub intermission_ms: u64,
+    #[serde(rename = "wave")]
+    pub waves: Vec<Wave>,
+}
+
+impl Waves {
+    /// Names of every spawn marker the waves ask for
+    pub fn markers(&self) -> impl Iterator<Item = &str> {
+        self.waves
+            .iter()
+            .flat_map(|wave| wave.groups.iter())
+            .filter_map(|group| match &group.spawn {
+                SpawnPoint::Marker(name) => Some(name.as_str()),
+                _ => None,
+            })
+    }
+    /// Names of every goblin kind the waves ask for
+    pub fn kinds(&self) -> impl Iterator<Item = &str> {
+        self.waves
+            .iter()
+            .flat_map(|wave| wave.groups.iter())
+            .filter_map(|group| group.kind.as_deref())
+    }
+}
+
+/// A goblin the director wants spawned
+#[derive(Clone, Debug)]
+pub struct SpawnOrder {
+    pub kind: Option<String>,
+    pub spawn: SpawnPoint,
+}
+
+/// What the director is doing, for showing to the player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    /// Counting down to wave `next`, numbered from 1
+    Intermission { next: u32, left: Duration },
+    /// Wave `number` is spawning or still has goblins alive
+    Wave { number: u32 },
+}
+
+/// Progress through one group of the current wave
+struct GroupState {
+    spawned: u32,
+    /// Time until the next one is due
+    wait: Duration,
+}
+
+/// Decides when goblins spawn, wave after wave
+///
+/// Once the last wave is cleared the waves start over, with the wave number still counting up
+pub struct Director {
+    waves: Waves,
+    /// Waves started so far
+    number: u32,
+    in_wave: bool,
+    intermission_left: Duration,
+    groups: Vec<GroupState>,
+}
+
+impl Director {
+    pub fn new(waves: Waves) -> Director {
+        let intermission_left = Duration::from_millis(waves.intermission_ms);
+        Director {
+            waves,
+            number: 0,
+            in_wave: false,
+            intermission_left,
+            groups: Vec::new(),
+        }
+    }
+    pub fn phase(&self) -> Phase {
+        if !self.in_wave {
+            Phase::Intermission {
+                next: self.number + 1,
+                left: self.intermission_left,
+            }
+        } else {
+            Phase::Wave { number: self.number }
+        }
+    }
+    /// The wave being fought, or the last one fought during an intermission, counting from 1
+    pub fn wave(&self) -> u32 {
+        self.number
+    }
+    /// Moves on by `dt` with `alive` goblins still in the world, and returns the goblins due to spawn
+    ///
+    /// Goblins within a wave come `pace` times as fast as the file says.
+    /// A wave is over once all its goblins have spawned and none are left alive
+    pub fn update(&mut self, dt: Duration, alive: usize, pace: f32) -> Vec<SpawnOrder> {
+        let mut orders = Vec::new();
+        if self.waves.waves.is_empty() {
+            return orders;
+        }
+
+        let mut dt = dt;
+        if !self.in_wave {
+            if self.intermission_left > dt {
+                self.intermission_left -= dt;
+                return orders;
+            }
+            // Only the time left over from the intermission counts towards the wave
+            dt -= self.intermission_left;
+            self.start_wave();
+        }
+
+        let wave = &self.waves.waves[(self.number - 1) as usize % self.waves.waves.len()];
+        let mut room = self.waves.max_alive.saturating_sub(alive);
+        for (group, state) in wave.groups.iter().zip(self.groups.iter_mut()) {
+            state.wait = state.wait.saturating_sub(dt.mul_f32(pace));
+            // A full world holds the group back, and it carries on once there's room
+            while state.spawned < group.count && state.wait == Duration::ZERO && room > 0 {
+                orders.push(SpawnOrder {
+                    kind: group.kind.clone(),
+                    spawn: group.spawn.clone(),
+                });
+                state.spawned += 1;
+                state.wait = Duration::from_millis(group.interval_ms);
+                room -= 1;
+            }
+        }
+
+        let finished = wave
+            .groups
+            .iter()
+            .zip(self.groups.iter())
+            .all(|(group, state)| state.spawned >= group.count);
+        if finished && alive == 0 && orders.is_empty() {
+            self.in_wave = false;
+            self.intermission_left = Duration::from_millis(self.waves.intermission_ms);
+        }
+        orders
+    }
+    fn start_wave(&mut self) {
+        self.number += 1;
+        self.in_wave = true;
+        let wave = &self.waves.waves[(self.number - 1) as usize % self.waves.waves.len()];
+        self.groups = wave
+            .groups
+            .iter()
+            .map(|group| GroupState {
+                spawned: 0,
+                wait: Duration::from_millis(group.delay_ms),
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    /// Two waves of grunts: three coming a second apart after a short delay, then one brute
+    fn waves(max_alive: usize) -> Waves {
+        toml::from_str(&format!(
+            r#"
+            max_alive = {}
+            intermission_ms = 1000
+
+            [[wave]]
+            [[wave.group]]
+            kind = "grunt"
+            count = 3
+            delay_ms = 500
+            interval_ms = 1000
+
+            [[wave]]
+            [[wave.group]]
+            kind = "brute"
+            count = 1
+            "#,
+            max_alive
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn intermission_counts_down_to_the_first_wave() {
+        let mut director = Director::new(waves(10));
+        assert_eq!(
+            director.phase(),
+            Phase::Intermission {
+                next: 1,
+                left: 1000 * MS
+            }
+        );
+        assert!(director.update(400 * MS, 0, 1.0).is_empty());
+        assert_eq!(
+            director.phase(),
+            Phase::Intermission {
+                next: 1,
+                left: 600 * MS
+            }
+        );
+        director.update(600 * MS, 0, 1.0);
+        assert_eq!(director.phase(), Phase::Wave { number: 1 });
+    }
+
+    #[test]
+    fn groups_wait_for_their_delay_then_their_interval() {
+        let mut director = Director::new(waves(10));
+        director.update(1000 * MS, 0, 1.0);
+        assert!(director.update(499 * MS, 0, 1.0).is_empty());
+        assert_eq!(director.update(MS, 0, 1.0).len(), 1);
+        assert!(director.update(999 * MS, 1, 1.0).is_empty());
+        assert_eq!(director.update(MS, 1, 1.0).len(), 1);
+
+        // At double pace the interval takes half as long
+        assert_eq!(director.update(500 * MS, 2, 2.0).len(), 1);
+    }
+
+    #[test]
+    fn spawning_holds_back_while_the_world_is_full() {
+        let mut director = Director::new(waves(1));
+        director.update(1000 * MS, 0, 1.0);
+        assert_eq!(director.update(500 * MS, 0, 1.0).len(), 1);
+        assert!(director.update(5000 * MS, 1, 1.0).is_empty());
+        // The held back goblin comes as soon as there's room
+        assert_eq!(director.update(MS, 0, 1.0).len(), 1);
+    }
+
+    #[test]
+    fn waves_end_once_everything_has_spawned_and_died() {
+        let mut director = Director::new(waves(10));
+        director.update(1000 * MS, 0, 1.0);
+        let mut spawned = 0;
+        for _ in 0..3 {
+            spawned += director.update(1000 * MS, spawned, 1.0).len();
+        }
+        assert_eq!(spawned, 3);
+        director.update(MS, 2, 1.0);
+        assert_eq!(director.phase(), Phase::Wave { number: 1 });
+        director.update(MS, 0, 1.0);
+        assert_eq!(
+            director.phase(),
+            Phase::Intermission {
+                next: 2,
+                left: 1000 * MS
+            }
+        );
+    }
+
+    #[test]
+    fn waves_loop_with_the_number_counting_up() {
+        let mut director = Director::new(waves(10));
+        let mut kinds = Vec::new();
+        for _ in 0..100 {
+            let orders = director.update(500 * MS, 0, 1.0);
+            kinds.extend(orders.into_iter().filter_map(|order| order.kind));
+            if kinds.len() == 5 {
+                break;
+            }
+        }
+        assert_eq!(director.wave(), 3);
+        assert_eq!(kinds, ["grunt", "grunt", "grunt", "brute", "grunt"]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Makes up levels from a seed
+//!
+//! The ground is a heightmap that wanders up and down a tile at a time, with the odd gap to jump,
+//! ledges floating above it and grass scattered along the top. The same seed always makes the same level.
+
+use std::ops::RangeInclusive;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{utility::sheet::Rgb, world::terrain::TILE_SIZE};
+
+use super::{cell_pos, Level, SpawnMarker};
+
+/// Columns either side of the player start, which is in column 0
+const LEFT: i32 = -30;
+const RIGHT: i32 = 90;
+
+/// Column the "far left" spawn marker is in
+const FAR_LEFT: i32 = -19;
+
+/// Rows the top of the ground stays between, so it stays on screen
+const SURFACE_ROWS: RangeInclusive<i32> = -1..=4;
+
+/// Ground goes down to this row, and nothing is higher than the top row
+const TOP_ROW: i32 = -6;
+const BOTTOM_ROW: i32 = 6;
+
+/// The ground stays level for this many columns before it might step up or down
+const FLAT_RUN: RangeInclusive<i32> = 4..=10;
+
+/// Gaps are this many columns wide, which the player can always jump
+const GAP_WIDTH: RangeInclusive<i32> = 2..=3;
+const GAP_CHANCE: f64 = 0.3;
+
+/// Ground this many columns either side of the start and at the ends is never a gap,
+/// and neither is the ground under the far left spawn
+const SAFE_COLUMNS: i32 = 8;
+
+/// Ledges are this many tiles long and sit this many rows above the highest ground under them,
+/// which leaves room to walk under them and is still in jumping reach
+const LEDGE_LENGTH: RangeInclusive<i32> = 3..=5;
+const LEDGE_RISE: i32 = 3;
+
+/// Columns from the end of one ledge to the start of the next
+const LEDGE_SPACING: RangeInclusive<i32> = 8..=20;
+const LEDGE_CHANCE: f64 = 0.6;
+
+/// Chance of a tuft of grass on top of any column of ground
+const GRASS_CHANCE: f64 = 0.2;
+
+/// Tiles the ground is built from
+const SURFACE_TILES: [u16; 3] = [0, 1, 2];
+const FILL_TILE: u16 = 13;
+const LEDGE_TILE: u16 = 5;
+const GRASS_TILE: u16 = 14;
+
+/// Makes the level for `seed`, with the spawn markers the waves file uses
+pub fn generate(seed: u64) -> Level {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let width = (RIGHT - LEFT + 1) as usize;
+    let height = (BOTTOM_ROW - TOP_ROW + 1) as usize;
+    let mut level = Level {
+        name: format!("Wilds {}", seed),
+        background: Rgb([0, 255, 255]),
+        player_start: (0.0, 0.0).into(),
+        origin: (LEFT, TOP_ROW),
+        tiles: vec![vec![None; width]; height],
+        passable: vec![GRASS_TILE],
+        spawns: Vec::new(),
+        triggers: Vec::new(),
+    };
+
+    let surface = heightmap(&mut rng);
+    for (column, top) in surface.iter().enumerate() {
+        let top = match top {
+            Some(top) => *top,
+            None => continue,
+        };
+        let x = LEFT + column as i32;
+        level.set_tile((x, top), Some(SURFACE_TILES[rng.gen_range(0..SURFACE_TILES.len())]));
+        for y in top + 1..=BOTTOM_ROW {
+            level.set_tile((x, y), Some(FILL_TILE));
+        }
+        if rng.gen_bool(GRASS_CHANCE) {
+            level.set_tile((x, top - 1), Some(GRASS_TILE));
+        }
+    }
+
+    let ledges = ledges(&mut rng, &surface);
+    for &(start, length, row) in ledges.iter() {
+        for x in start..start + length {
+            level.set_tile((x, row), Some(LEDGE_TILE));
+        }
+    }
+
+    // Things start a couple of tiles above the ground, and drop onto it
+    let above = |column: i32, row: i32| {
+        let pos = cell_pos((column, row - 2));
+        (pos.x + TILE_SIZE / 2.0, pos.y)
+    };
+    let ground_at = |column: i32| surface[(column - LEFT) as usize].unwrap_or(*SURFACE_ROWS.end());
+    level.player_start = above(0, ground_at(0)).into();
+    level.spawns.push(SpawnMarker {
+        name: String::from("far left"),
+        pos: above(FAR_LEFT, ground_at(FAR_LEFT)),
+    });
+    // The ledge closest to the start, ahead of the player
+    let (start, length, row) = ledges
+        .iter()
+        .copied()
+        .find(|&(start, _, _)| start > 0)
+        .or_else(|| ledges.last().copied())
+        .unwrap_or((0, 1, ground_at(0)));
+    level.spawns.push(SpawnMarker {
+        name: String::from("ledge"),
+        pos: above(start + length / 2, row),
+    });
+    level
+}
+
+/// Top row of the ground in each column from `LEFT` to `RIGHT`, or `None` for a gap
+fn heightmap(rng: &mut StdRng) -> Vec<Option<i32>> {
+    let mut surface = Vec::new();
+    let mut top = (SURFACE_ROWS.start() + SURFACE_ROWS.end()) / 2;
+    let mut column = LEFT;
+    while column <= RIGHT {
+        let run = rng.gen_range(FLAT_RUN);
+        for _ in 0..run {
+            surface.push(Some(top));
+        }
+        column += run;
+
+        let gap = rng.gen_range(GAP_WIDTH);
+        let safe = |x: i32| {
+            x.abs() <= SAFE_COLUMNS
+                || x == FAR_LEFT
+                || !(LEFT + SAFE_COLUMNS..=RIGHT - SAFE_COLUMNS).contains(&x)
+        };
+        if rng.gen_bool(GAP_CHANCE) && !(column..column + gap).any(safe) {
+            for _ in 0..gap {
+                surface.push(None);
+            }
+            column += gap;
+        } else {
+            // Gaps keep the ground at the same height on both sides, so the jump is never a climb
+            top = (top + rng.gen_range(-1..=1)).clamp(*SURFACE_ROWS.start(), *SURFACE_ROWS.end());
+        }
+    }
+    surface.truncate((RIGHT - LEFT + 1) as usize);
+    surface
+}
+
+/// Ledges as `(first column, length, row)`, left to right
+fn ledges(rng: &mut StdRng, surface: &[Option<i32>]) -> Vec<(i32, i32, i32)> {
+    let mut ledges = Vec::new();
+    let mut column = LEFT + rng.gen_range(LEDGE_SPACING);
+    while column <= RIGHT {
+        let length = rng.gen_range(LEDGE_LENGTH).min(RIGHT - column + 1);
+        if rng.gen_bool(LEDGE_CHANCE) {
+            let highest = (column..column + length)
+                .filter_map(|x| surface[(x - LEFT) as usize])
+                .min()
+                .unwrap_or(*SURFACE_ROWS.end());
+            ledges.push((column, length, (highest - LEDGE_RISE).max(TOP_ROW)));
+        }
+        column += length + rng.gen_range(LEDGE_SPACING);
+    }
+    ledges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_make_the_same_level_every_time() {
+        assert_eq!(generate(7), generate(7));
+        assert_ne!(generate(7).tiles, generate(8).tiles);
+    }
+
+    #[test]
+    fn there_is_ground_around_the_start_and_the_far_left_spawn() {
+        for seed in 0..50 {
+            let surface = heightmap(&mut StdRng::seed_from_u64(seed));
+            assert_eq!(surface.len(), (RIGHT - LEFT + 1) as usize);
+            let ground = |x: i32| surface[(x - LEFT) as usize].is_some();
+            for x in -SAFE_COLUMNS..=SAFE_COLUMNS {
+                assert!(ground(x), "seed {} has a gap in column {}", seed, x);
+            }
+            assert!(ground(FAR_LEFT), "seed {} has a gap under the far left spawn", seed);
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use std::{collections::BTreeMap, convert::TryFrom, iter, path::Path, sync::OnceLock};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::GameError,
+    utility::{
+        asset_path,
+        assets::{self, AssetError},
+        sheet::Rgb,
+    },
+};
+
+use super::{
+    collision::GameRect,
+    space::GamePos,
+    terrain::{TileGrid, TILE_SIZE},
+};
+
+pub mod generate;
+pub mod tiled;
+
+/// The level a run starts on, unless another is set with `set_start_level`
+pub const DEFAULT_LEVEL: &str = "levels/plains.toml";
+
+/// Start level that's made up from the run's seed rather than read from a file
+pub const GENERATED_LEVEL: &str = "generated";
+
+static START_LEVEL: OnceLock<String> = OnceLock::new();
+
+/// Tiles across tiles.png, which tile indices wrap at
+pub const TILESET_COLUMNS: u16 = 4;
+
+/// Tiles in tiles.png
+pub const TILESET_TILES: u16 = 16;
+
+/// A named spot in a level that something spawns at
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SpawnMarker {
+    pub name: String,
+    pub pos: (f32, f32),
+}
+
+/// A named area of a level for something to happen in, with whatever properties its designer gave it
+///
+/// Nothing acts on triggers yet, but they show with the rest of the debug overlay
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trigger {
+    pub name: String,
+    pub area: GameRect,
+    pub properties: BTreeMap<String, String>,
+}
+
+/// How a trigger is written down, with its area as `[x, y, width, height]`
+#[derive(Deserialize, Serialize)]
+struct TriggerFile {
+    name: String,
+    area: (f32, f32, f32, f32),
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    properties: BTreeMap<String, String>,
+}
+
+/// A stage to play on, as laid out in a level file
+///
+/// ```toml
+/// name = "Plains"
+/// background = "#00ffff"
+/// player_start = [0.0, 0.0]
+/// origin = [-2, 1]
+/// tiles = [
+///     ". . . . 4",
+///     "0 1 2 . 5",
+/// ]
+///
+/// [[spawn]]
+/// name = "cave"
+/// pos = [40.0, 0.0]
+///
+/// [[trigger]]
+/// name = "door"
+/// area = [60.0, -10.0, 5.0, 10.0]
+/// properties = { leads_to = "caves" }
+/// ```
+///
+/// Each row of `tiles` lists a tile per cell, as its index in the tileset counting across then down,
+/// or `.` for no tile, and `origin` is the cell of the first tile in the first row
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(try_from = "LevelFile")]
+pub struct Level {
+    pub name: String,
+    pub background: Rgb,
+    pub player_start: GamePos,
+    /// Cell of the top left of `tiles`
+    pub origin: (i32, i32),
+    /// Rows of tile indices, top to bottom
+    pub tiles: Vec<Vec<Option<u16>>>,
+    /// Tile indices that are drawn but can be walked through
+    pub passable: Vec<u16>,
+    pub spawns: Vec<SpawnMarker>,
+    pub triggers: Vec<Trigger>,
+}
+
+/// How a level is written down, before its tile rows are read
+#[derive(Deserialize, Serialize)]
+struct LevelFile {
+    name: String,
+    background: Rgb,
+    player_start: (f32, f32),
+    origin: (i32, i32),
+    tiles: Vec<String>,
+    #[serde(default)]
+    passable: Vec<u16>,
+    // Empty lists are left out, since TOML can't have a plain value after a table
+    #[serde(default, rename = "spawn", skip_serializing_if = "Vec::is_empty")]
+    spawns: Vec<SpawnMarker>,
+    #[serde(default, rename = "trigger", skip_serializing_if = "Vec::is_empty")]
+    triggers: Vec<TriggerFile>,
+}
+
+impl TryFrom<LevelFile> for Level {
+    type Error = String;
+
+    fn try_from(file: LevelFile) -> Result<Self, Self::Error> {
+        let tiles = file
+            .tiles
+            .iter()
+            .enumerate()
+            .map(|(row, line)| {
+                line.split_whitespace()
+                    .enumerate()
+                    .map(|(column, tile)| match tile {
+                        "." => Ok(None),
+                        index => match index.parse::<u16>() {
+                            Ok(index) if index < TILESET_TILES => Ok(Some(index)),
+                            Ok(index) => Err(format!(
+                                "tile row {} column {} is {}, but the tileset only has {} tiles",
+                                row + 1,
+                                column + 1,
+                                index,
+                                TILESET_TILES
+                            )),
+                            Err(_) => Err(format!(
+                                "tile row {} column {} is {}, which is neither a tile index nor .",
+                                row + 1,
+                                column + 1,
+                                index
+                            )),
+                        },
+                    })
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Level {
+            name: file.name,
+            background: file.background,
+            player_start: file.player_start.into(),
+            origin: file.origin,
+            tiles,
+            passable: file.passable,
+            spawns: file.spawns,
+            triggers: file
+                .triggers
+                .into_iter()
+                .map(|trigger| Trigger {
+                    name: trigger.name,
+                    area: GameRect::new(
+                        (trigger.area.0, trigger.area.1).into(),
+                        (trigger.area.2, trigger.area.3),
+                    ),
+                    properties: trigger.properties,
+                })
+                .collect(),
+        })
+    }
+}
+
+impl From<&Level> for LevelFile {
+    fn from(level: &Level) -> Self {
+        LevelFile {
+            name: level.name.clone(),
+            background: level.background,
+            player_start: level.player_start.into(),
+            origin: level.origin,
+            tiles: level
+                .tiles
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|tile| match tile {
+                            Some(index) => index.to_string(),
+                            None => String::from("."),
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect(),
+            passable: level.passable.clone(),
+            spawns: level.spawns.clone(),
+            triggers: level
+                .triggers
+                .iter()
+                .map(|trigger| TriggerFile {
+                    name: trigger.name.clone(),
+                    area: (
+                        trigger.area.min.x,
+                        trigger.area.min.y,
+                        trigger.area.width(),
+                        trigger.area.height(),
+                    ),
+                    properties: trigger.properties.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Level {
+    /// Every tile in the level, with the cell it's in
+    pub fn cells(&self) -> impl Iterator<Item = ((i32, i32), u16)> + '_ {
+        let origin = self.origin;
+        self.tiles.iter().enumerate().flat_map(move |(row, tiles)| {
+            tiles.iter().enumerate().filter_map(move |(column, tile)| {
+                tile.map(|tile| ((origin.0 + column as i32, origin.1 + row as i32), tile))
+            })
+        })
+    }
+    /// Which of the level's cells are solid
+    pub fn terrain(&self) -> TileGrid {
+        let mut grid = TileGrid::new();
+        for (cell, tile) in self.cells() {
+            if !self.passable.contains(&tile) {
+                grid.set_solid(cell, true);
+            }
+        }
+        grid
+    }
+    /// Bottom edge of the lowest row of tiles, in units
+    pub fn bottom(&self) -> f32 {
+        (self.origin.1 + self.tiles.len() as i32) as f32 * TILE_SIZE
+    }
+    pub fn spawn(&self, name: &str) -> Option<GamePos> {
+        self.spawns
+            .iter()
+            .find(|spawn| spawn.name == name)
+            .map(|spawn| spawn.pos.into())
+    }
+    pub fn tile(&self, cell: (i32, i32)) -> Option<u16> {
+        let row = usize::try_from(cell.1 - self.origin.1).ok()?;
+        let column = usize::try_from(cell.0 - self.origin.0).ok()?;
+        self.tiles.get(row)?.get(column).copied().flatten()
+    }
+    /// Puts `tile` in `cell`, growing the level to fit it
+    pub fn set_tile(&mut self, cell: (i32, i32), tile: Option<u16>) {
+        if tile.is_some() {
+            self.grow_to(cell);
+        }
+        let row = usize::try_from(cell.1 - self.origin.1).ok();
+        let column = usize::try_from(cell.0 - self.origin.0).ok();
+        if let (Some(row), Some(column)) = (row, column) {
+            if let Some(slot) = self.tiles.get_mut(row).and_then(|tiles| tiles.get_mut(column)) {
+                *slot = tile;
+            }
+        }
+    }
+    /// Adds empty rows and columns until `cell` is inside `tiles`, evening out any short rows on the way
+    fn grow_to(&mut self, cell: (i32, i32)) {
+        if self.tiles.is_empty() {
+            self.origin = cell;
+            self.tiles = vec![vec![None]];
+            return;
+        }
+        let width = self.tiles.iter().map(Vec::len).max().unwrap_or(0) as i32;
+        let height = self.tiles.len() as i32;
+        let left = (self.origin.0 - cell.0).max(0) as usize;
+        let top = (self.origin.1 - cell.1).max(0) as usize;
+        let right = (cell.0 - (self.origin.0 + width - 1)).max(0) as usize;
+        let bottom = (cell.1 - (self.origin.1 + height - 1)).max(0) as usize;
+
+        let new_width = left + width as usize + right;
+        for row in self.tiles.iter_mut() {
+            row.splice(0..0, iter::repeat(None).take(left));
+            row.resize(new_width, None);
+        }
+        self.tiles.splice(0..0, iter::repeat(vec![None; new_width]).take(top));
+        self.tiles.resize(self.tiles.len() + bottom, vec![None; new_width]);
+        self.origin = (self.origin.0 - left as i32, self.origin.1 - top as i32);
+    }
+    /// Writes the level to `path` as a level file, returning where on disk it went
+    pub fn save(&self, path: &str) -> Result<String, AssetError> {
+        assets::save_data(path, &LevelFile::from(self))
+    }
+}
+
+/// Reads the level at `path`, which is either one of the game's level files or a map made in Tiled
+pub fn load(path: &str) -> Result<Level, GameError> {
+    let import = match Path::new(path).extension().and_then(|e| e.to_str()) {
+        Some("tmx") => tiled::import_tmx,
+        Some("tmj") => tiled::import_tmj,
+        _ => return Ok(assets::load_data(path)?),
+    };
+    let text = asset_path::read_to_string(path)?;
+    let name = Path::new(path)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(path);
+    import(&text, name).map_err(|error| GameError::Import {
+        path: path.to_string(),
+        error,
+    })
+}
+
+/// Like `load`, except that `GENERATED_LEVEL` makes up a level from `seed`
+pub fn load_seeded(path: &str, seed: u64) -> Result<Level, GameError> {
+    match path {
+        GENERATED_LEVEL => Ok(generate::generate(seed)),
+        path => load(path),
+    }
+}
+
+/// Makes runs start on the level at `path` instead of the default one
+///
+/// Has no effect once a run has started
+pub fn set_start_level(path: String) {
+    let _ = START_LEVEL.set(path);
+}
+
+pub fn start_level() -> &'static str {
+    START_LEVEL.get_or_init(|| DEFAULT_LEVEL.to_string())
+}
+
+/// Where a level loaded from `path` is saved, which is `path` itself unless it's a Tiled map or generated
+pub fn save_path(path: &str) -> String {
+    let file = Path::new(path);
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("toml") => path.to_string(),
+        _ => {
+            let stem = file.file_stem().and_then(|stem| stem.to_str()).unwrap_or("level");
+            format!("levels/{}.toml", stem)
+        }
+    }
+}
+
+/// Which cell `pos` is in
+pub fn cell_at(pos: GamePos) -> (i32, i32) {
+    ((pos.x / TILE_SIZE).floor() as i32, (pos.y / TILE_SIZE).floor() as i32)
+}
+
+/// Where the tile at `index` is on the tileset, in frames
+pub fn tile_frame(index: u16) -> (u16, u16) {
+    (index % TILESET_COLUMNS, index / TILESET_COLUMNS)
+}
+
+/// Top left of `cell`, in units
+pub fn cell_pos(cell: (i32, i32)) -> GamePos {
+    (cell.0 as f32 * TILE_SIZE, cell.1 as f32 * TILE_SIZE).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(tiles: &str) -> Result<Level, toml::de::Error> {
+        toml::from_str(&format!(
+            "name = \"Test\"\nbackground = \"#00ffff\"\nplayer_start = [0.0, 0.0]\n\
+             origin = [0, 0]\ntiles = [{}]\n",
+            tiles
+        ))
+    }
+
+    #[test]
+    fn tile_rows_are_read() {
+        let level = parse(r#"". 15", "0 .""#).unwrap();
+        assert_eq!(level.tiles, vec![vec![None, Some(15)], vec![Some(0), None]]);
+    }
+
+    #[test]
+    fn tiles_outside_the_tileset_are_refused_where_they_are() {
+        let error = parse(r#"". 1", "2 . 16""#).unwrap_err().to_string();
+        assert!(error.contains("row 2 column 3"), "{}", error);
+    }
+
+    #[test]
+    fn tiles_that_are_not_numbers_are_refused() {
+        let error = parse(r#""x""#).unwrap_err().to_string();
+        assert!(error.contains("row 1 column 1"), "{}", error);
+    }
+
+    #[test]
+    fn growing_keeps_tiles_where_they_were() {
+        let mut level = parse(r#""1 2", "3 4""#).unwrap();
+        level.origin = (5, -3);
+        // Left, up, right and down
+        level.set_tile((3, -3), Some(5));
+        assert_eq!(level.origin, (3, -3));
+        level.set_tile((5, -5), Some(6));
+        assert_eq!(level.origin, (3, -5));
+        level.set_tile((7, -3), Some(7));
+        level.set_tile((4, 0), Some(8));
+        assert_eq!(level.origin, (3, -5));
+        assert_eq!(level.tiles.len(), 6);
+        assert!(level.tiles.iter().all(|row| row.len() == 5));
+        for (cell, tile) in [
+            ((5, -3), 1),
+            ((6, -3), 2),
+            ((5, -2), 3),
+            ((6, -2), 4),
+            ((3, -3), 5),
+            ((5, -5), 6),
+            ((7, -3), 7),
+            ((4, 0), 8),
+        ] {
+            assert_eq!(level.tile(cell), Some(tile), "{:?}", cell);
+        }
+        assert_eq!(level.cells().count(), 8);
+    }
+
+    #[test]
+    fn growing_an_empty_level_starts_it_at_the_cell() {
+        let mut level = parse("").unwrap();
+        level.set_tile((-4, 7), Some(3));
+        assert_eq!(level.origin, (-4, 7));
+        assert_eq!(level.tiles, vec![vec![Some(3)]]);
+    }
+
+    #[test]
+    fn saved_levels_load_the_same() {
+        let mut properties = BTreeMap::new();
+        properties.insert(String::from("leads_to"), String::from("caves"));
+        let level = Level {
+            name: String::from("Plains"),
+            background: Rgb([0, 255, 255]),
+            player_start: (1.5, -2.0).into(),
+            origin: (-2, 1),
+            tiles: vec![vec![None, None, Some(4)], vec![Some(0), Some(15), None]],
+            passable: vec![14],
+            spawns: vec![SpawnMarker {
+                name: String::from("cave"),
+                pos: (40.0, 0.0),
+            }],
+            triggers: vec![
+                Trigger {
+                    name: String::from("door"),
+                    area: GameRect::new((60.0, -10.0).into(), (5.0, 10.0)),
+                    properties,
+                },
+                Trigger {
+                    name: String::from("pit"),
+                    area: GameRect::new((0.0, 20.0).into(), (30.0, 5.0)),
+                    properties: BTreeMap::new(),
+                },
+            ],
+        };
+        // The same text save writes
+        let text = toml::to_string_pretty(&LevelFile::from(&level)).unwrap();
+        assert_eq!(toml::from_str::<Level>(&text).unwrap(), level);
+    }
+}
@@ -0,0 +1,791 @@
+//! Turns maps made in Tiled into levels
+//!
+//! Maps have to be orthogonal and fixed size, with a single tileset of tiles.png embedded in them
+//! and their tile layers saved as CSV. Tile layers are stacked into the level's tiles in order,
+//! and objects are read by their class:
+//!
+//! - `player_start`, where the player starts
+//! - `spawn`, a spawn marker named after the object
+//! - `trigger`, a trigger covering the object's rectangle, with the object's custom properties
+//!
+//! Tiles with a custom `solid` property set to false are passable, and a custom `name` property
+//! on the map names the level. Everything else in the map is ignored.
+
+use std::{collections::BTreeMap, convert::TryFrom, fmt};
+
+use serde::{de::IgnoredAny, Deserialize};
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::{
+    utility::sheet::Rgb,
+    world::{collision::GameRect, space::GamePos, terrain::TILE_SIZE},
+};
+
+use super::{Level, SpawnMarker, Trigger, TILESET_COLUMNS, TILESET_TILES};
+
+/// Background of maps that don't set their own
+const DEFAULT_BACKGROUND: Rgb = Rgb([0, 255, 255]);
+
+/// Tiled keeps whether a tile is flipped in the top bits of its global id
+const FLIP_FLAGS: u32 = 0xf000_0000;
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// The file isn't valid XML or JSON
+    Syntax(String),
+    /// Something the map needs isn't there
+    Missing(String),
+    /// The map uses a Tiled feature levels can't represent
+    Unsupported(String),
+    /// Something in the map doesn't make sense
+    Invalid(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Syntax(message) => write!(f, "{}", message),
+            ImportError::Missing(what) => write!(f, "the map has no {}", what),
+            ImportError::Unsupported(what) => write!(f, "levels can't have {}", what),
+            ImportError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Imports a map saved as TMX, naming the level `name` unless the map names itself
+pub fn import_tmx(text: &str, name: &str) -> Result<Level, ImportError> {
+    let root = Element::parse(text)?;
+    if root.name != "map" {
+        return Err(ImportError::Missing(String::from("<map> element")));
+    }
+    to_level(Map::from_tmx(&root)?, name)
+}
+
+/// Imports a map saved as JSON, naming the level `name` unless the map names itself
+pub fn import_tmj(text: &str, name: &str) -> Result<Level, ImportError> {
+    let map: TmjMap = serde_json::from_str(text).map_err(|e| ImportError::Syntax(e.to_string()))?;
+    to_level(Map::try_from(map)?, name)
+}
+
+/// Everything a level needs from a map, whichever format it was saved in
+struct Map {
+    orientation: String,
+    infinite: bool,
+    width: usize,
+    height: usize,
+    tile_width: f32,
+    tile_height: f32,
+    background: Option<String>,
+    properties: BTreeMap<String, String>,
+    tilesets: Vec<Tileset>,
+    layers: Vec<Layer>,
+}
+
+struct Tileset {
+    first_gid: u32,
+    /// File the tileset was saved to, if it isn't embedded in the map
+    source: Option<String>,
+    columns: Option<u32>,
+    tile_count: Option<u32>,
+    /// Custom properties of the tiles that have any, by local id
+    tiles: Vec<(u32, BTreeMap<String, String>)>,
+}
+
+enum Layer {
+    /// Global ids of each cell, across then down, where 0 is empty
+    Tiles {
+        name: String,
+        data: Vec<u32>,
+    },
+    Objects(Vec<Object>),
+}
+
+struct Object {
+    name: String,
+    class: String,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    properties: BTreeMap<String, String>,
+}
+
+fn to_level(map: Map, name: &str) -> Result<Level, ImportError> {
+    if map.orientation != "orthogonal" {
+        return Err(ImportError::Unsupported(format!(
+            "{} maps",
+            map.orientation
+        )));
+    }
+    if map.infinite {
+        return Err(ImportError::Unsupported(String::from("infinite maps")));
+    }
+    let tileset = match &map.tilesets[..] {
+        [] => return Err(ImportError::Missing(String::from("tileset"))),
+        [tileset] => tileset,
+        _ => {
+            return Err(ImportError::Unsupported(String::from(
+                "more than one tileset",
+            )))
+        }
+    };
+    if let Some(source) = &tileset.source {
+        return Err(ImportError::Unsupported(format!(
+            "external tilesets like {}, so embed it in the map",
+            source
+        )));
+    }
+    if tileset
+        .columns
+        .is_some_and(|columns| columns != TILESET_COLUMNS as u32)
+    {
+        return Err(ImportError::Invalid(format!(
+            "the tileset should be tiles.png, which is {} tiles across",
+            TILESET_COLUMNS
+        )));
+    }
+
+    let mut tiles = vec![vec![None; map.width]; map.height];
+    for layer in map.layers.iter() {
+        let (layer_name, data) = match layer {
+            Layer::Tiles { name, data } => (name, data),
+            Layer::Objects(_) => continue,
+        };
+        if data.len() != map.width * map.height {
+            return Err(ImportError::Invalid(format!(
+                "layer {} has {} tiles, but the map is {} by {}",
+                layer_name,
+                data.len(),
+                map.width,
+                map.height
+            )));
+        }
+        for (i, &gid) in data.iter().enumerate() {
+            // Tiles are never drawn flipped, so the flags are dropped
+            let gid = gid & !FLIP_FLAGS;
+            if gid == 0 {
+                continue;
+            }
+            let index = gid
+                .checked_sub(tileset.first_gid)
+                .filter(|&index| tileset.tile_count.map_or(true, |count| index < count))
+                .filter(|&index| index < TILESET_TILES as u32)
+                .and_then(|index| u16::try_from(index).ok())
+                .ok_or_else(|| {
+                    ImportError::Invalid(format!(
+                        "layer {} has tile {}, which isn't in the tileset",
+                        layer_name, gid
+                    ))
+                })?;
+            tiles[i / map.width][i % map.width] = Some(index);
+        }
+    }
+
+    let passable = tileset
+        .tiles
+        .iter()
+        .filter(|(_, properties)| properties.get("solid").map(|solid| &solid[..]) == Some("false"))
+        .filter_map(|(id, _)| u16::try_from(*id).ok())
+        .collect();
+
+    // Objects are placed in pixels of the map's tiles
+    let scale = |x: f32, y: f32| -> GamePos {
+        (
+            x / map.tile_width * TILE_SIZE,
+            y / map.tile_height * TILE_SIZE,
+        )
+            .into()
+    };
+    let mut player_start = None;
+    let mut spawns = Vec::new();
+    let mut triggers = Vec::new();
+    for layer in map.layers.iter() {
+        let objects = match layer {
+            Layer::Objects(objects) => objects,
+            Layer::Tiles { .. } => continue,
+        };
+        for object in objects.iter() {
+            let pos = scale(object.x, object.y);
+            match &object.class[..] {
+                "player_start" => player_start = Some(pos),
+                "spawn" => spawns.push(SpawnMarker {
+                    name: object.name.clone(),
+                    pos: pos.into(),
+                }),
+                "trigger" => {
+                    let size = scale(object.width, object.height);
+                    triggers.push(Trigger {
+                        name: object.name.clone(),
+                        area: GameRect::new(pos, size.into()),
+                        properties: object.properties.clone(),
+                    })
+                }
+                _ => (),
+            }
+        }
+    }
+
+    let background = match map
+        .background
+        .clone()
+        .or_else(|| map.properties.get("background").cloned())
+    {
+        // Tiled writes colours with alpha as #aarrggbb, and levels have no use for the alpha
+        Some(colour) => {
+            let digits = colour.trim_start_matches('#');
+            // Anything that isn't ASCII may not split there, and isn't a colour either way
+            let rgb = digits.get(digits.len().saturating_sub(6)..).unwrap_or(digits);
+            Rgb::try_from(format!("#{}", rgb)).map_err(ImportError::Invalid)?
+        }
+        None => DEFAULT_BACKGROUND,
+    };
+
+    Ok(Level {
+        name: map
+            .properties
+            .get("name")
+            .cloned()
+            .unwrap_or_else(|| name.to_string()),
+        background,
+        player_start: player_start
+            .ok_or_else(|| ImportError::Missing(String::from("object of class player_start")))?,
+        origin: (0, 0),
+        tiles,
+        passable,
+        spawns,
+        triggers,
+    })
+}
+
+impl Map {
+    fn from_tmx(map: &Element) -> Result<Map, ImportError> {
+        let mut tilesets = Vec::new();
+        for tileset in map.children("tileset") {
+            let tiles = tileset
+                .children("tile")
+                .map(|tile| Ok((tile.number("id")?, tile.properties())))
+                .collect::<Result<_, ImportError>>()?;
+            tilesets.push(Tileset {
+                first_gid: tileset.number("firstgid")?,
+                source: tileset.attribute("source").map(String::from),
+                columns: tileset
+                    .attribute("columns")
+                    .map(|_| tileset.number("columns"))
+                    .transpose()?,
+                tile_count: tileset
+                    .attribute("tilecount")
+                    .map(|_| tileset.number("tilecount"))
+                    .transpose()?,
+                tiles,
+            });
+        }
+
+        let mut layers = Vec::new();
+        collect_tmx_layers(map, &mut layers)?;
+
+        Ok(Map {
+            orientation: map
+                .attribute("orientation")
+                .unwrap_or("orthogonal")
+                .to_string(),
+            infinite: map.attribute("infinite") == Some("1"),
+            width: map.number("width")?,
+            height: map.number("height")?,
+            tile_width: map.number("tilewidth")?,
+            tile_height: map.number("tileheight")?,
+            background: map.attribute("backgroundcolor").map(String::from),
+            properties: map.properties(),
+            tilesets,
+            layers,
+        })
+    }
+}
+
+/// Adds the layers under `parent` to `layers` in order, including those inside groups
+fn collect_tmx_layers(parent: &Element, layers: &mut Vec<Layer>) -> Result<(), ImportError> {
+    for element in parent.children.iter() {
+        match &element.name[..] {
+            "layer" => {
+                let name = element.attribute("name").unwrap_or_default().to_string();
+                let data = element
+                    .children("data")
+                    .next()
+                    .ok_or_else(|| ImportError::Missing(format!("data for layer {}", name)))?;
+                match (data.attribute("encoding"), data.attribute("compression")) {
+                    (Some("csv"), None) => (),
+                    (encoding, compression) => {
+                        return Err(ImportError::Unsupported(format!(
+                            "layer {} saved as {}, so save it as CSV",
+                            name,
+                            compression.or(encoding).unwrap_or("XML")
+                        )))
+                    }
+                }
+                let data = data
+                    .text
+                    .split(',')
+                    .map(|gid| {
+                        gid.trim().parse().map_err(|_| {
+                            ImportError::Invalid(format!(
+                                "layer {} has {}, which isn't a tile",
+                                name,
+                                gid.trim()
+                            ))
+                        })
+                    })
+                    .collect::<Result<_, _>>()?;
+                layers.push(Layer::Tiles { name, data });
+            }
+            "objectgroup" => {
+                let mut objects = Vec::new();
+                for object in element.children("object") {
+                    objects.push(Object {
+                        name: object.attribute("name").unwrap_or_default().to_string(),
+                        // Tiled 1.9 saved the type as class, and later versions went back to type
+                        class: object
+                            .attribute("type")
+                            .or_else(|| object.attribute("class"))
+                            .unwrap_or_default()
+                            .to_string(),
+                        x: object.number("x")?,
+                        y: object.number("y")?,
+                        width: object
+                            .attribute("width")
+                            .map(|_| object.number("width"))
+                            .transpose()?
+                            .unwrap_or(0.0),
+                        height: object
+                            .attribute("height")
+                            .map(|_| object.number("height"))
+                            .transpose()?
+                            .unwrap_or(0.0),
+                        properties: object.properties(),
+                    });
+                }
+                layers.push(Layer::Objects(objects));
+            }
+            "group" => collect_tmx_layers(element, layers)?,
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// A map as saved to JSON, with only what levels use
+#[derive(Deserialize)]
+struct TmjMap {
+    #[serde(default = "default_orientation")]
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    backgroundcolor: Option<String>,
+    #[serde(default)]
+    properties: Vec<TmjProperty>,
+    tilesets: Vec<TmjTileset>,
+    layers: Vec<TmjLayer>,
+}
+
+#[derive(Deserialize)]
+struct TmjTileset {
+    firstgid: u32,
+    source: Option<String>,
+    columns: Option<u32>,
+    tilecount: Option<u32>,
+    #[serde(default)]
+    tiles: Vec<TmjTile>,
+}
+
+#[derive(Deserialize)]
+struct TmjTile {
+    id: u32,
+    #[serde(default)]
+    properties: Vec<TmjProperty>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum TmjLayer {
+    TileLayer {
+        #[serde(default)]
+        name: String,
+        data: TmjData,
+    },
+    ObjectGroup {
+        objects: Vec<TmjObject>,
+    },
+    Group {
+        layers: Vec<TmjLayer>,
+    },
+    /// Image layers, which levels have nothing to show with
+    #[serde(other)]
+    Other,
+}
+
+/// Tiles are an array when saved as CSV, and a string when encoded
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum TmjData {
+    Csv(Vec<u32>),
+    Encoded(IgnoredAny),
+}
+
+#[derive(Deserialize)]
+struct TmjObject {
+    #[serde(default)]
+    name: String,
+    /// Tiled 1.9 saved the type as class, and later versions went back to type
+    #[serde(default, rename = "type")]
+    kind: String,
+    #[serde(default)]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<TmjProperty>,
+}
+
+/// A custom property, which can be a string, number or bool
+#[derive(Deserialize)]
+struct TmjProperty {
+    name: String,
+    value: serde_json::Value,
+}
+
+fn default_orientation() -> String {
+    String::from("orthogonal")
+}
+
+impl TryFrom<TmjMap> for Map {
+    type Error = ImportError;
+
+    fn try_from(map: TmjMap) -> Result<Map, ImportError> {
+        let mut layers = Vec::new();
+        collect_tmj_layers(map.layers, &mut layers)?;
+        Ok(Map {
+            orientation: map.orientation,
+            infinite: map.infinite,
+            width: map.width,
+            height: map.height,
+            tile_width: map.tilewidth,
+            tile_height: map.tileheight,
+            background: map.backgroundcolor,
+            properties: tmj_properties(map.properties),
+            tilesets: map
+                .tilesets
+                .into_iter()
+                .map(|tileset| Tileset {
+                    first_gid: tileset.firstgid,
+                    source: tileset.source,
+                    columns: tileset.columns,
+                    tile_count: tileset.tilecount,
+                    tiles: tileset
+                        .tiles
+                        .into_iter()
+                        .map(|tile| (tile.id, tmj_properties(tile.properties)))
+                        .collect(),
+                })
+                .collect(),
+            layers,
+        })
+    }
+}
+
+/// Adds `tmj_layers` to `layers` in order, including those inside groups
+fn collect_tmj_layers(
+    tmj_layers: Vec<TmjLayer>,
+    layers: &mut Vec<Layer>,
+) -> Result<(), ImportError> {
+    for layer in tmj_layers {
+        match layer {
+            TmjLayer::TileLayer { name, data } => match data {
+                TmjData::Csv(data) => layers.push(Layer::Tiles { name, data }),
+                TmjData::Encoded(_) => {
+                    return Err(ImportError::Unsupported(format!(
+                        "layer {} saved as Base64, so save it as CSV",
+                        name
+                    )))
+                }
+            },
+            TmjLayer::ObjectGroup { objects } => layers.push(Layer::Objects(
+                objects
+                    .into_iter()
+                    .map(|object| Object {
+                        name: object.name,
+                        class: if object.kind.is_empty() {
+                            object.class
+                        } else {
+                            object.kind
+                        },
+                        x: object.x,
+                        y: object.y,
+                        width: object.width,
+                        height: object.height,
+                        properties: tmj_properties(object.properties),
+                    })
+                    .collect(),
+            )),
+            TmjLayer::Group { layers: group } => collect_tmj_layers(group, layers)?,
+            TmjLayer::Other => (),
+        }
+    }
+    Ok(())
+}
+
+/// Custom properties as text, whatever type they were given in Tiled
+fn tmj_properties(properties: Vec<TmjProperty>) -> BTreeMap<String, String> {
+    properties
+        .into_iter()
+        .map(|property| {
+            let value = match property.value {
+                serde_json::Value::String(s) => s,
+                value => value.to_string(),
+            };
+            (property.name, value)
+        })
+        .collect()
+}
+
+/// Just enough of an XML element to read a map from
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    /// Reads the root element of `text`
+    fn parse(text: &str) -> Result<Element, ImportError> {
+        // Elements still open, innermost last
+        let mut open: Vec<Element> = Vec::new();
+        for event in EventReader::from_str(text) {
+            match event.map_err(|e| ImportError::Syntax(e.to_string()))? {
+                XmlEvent::StartElement {
+                    name, attributes, ..
+                } => open.push(Element {
+                    name: name.local_name,
+                    attributes: attributes
+                        .into_iter()
+                        .map(|attribute| (attribute.name.local_name, attribute.value))
+                        .collect(),
+                    children: Vec::new(),
+                    text: String::new(),
+                }),
+                XmlEvent::EndElement { .. } => {
+                    let element = open.pop();
+                    match (element, open.last_mut()) {
+                        (Some(element), Some(parent)) => parent.children.push(element),
+                        (Some(element), None) => return Ok(element),
+                        (None, _) => break,
+                    }
+                }
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    if let Some(element) = open.last_mut() {
+                        element.text.push_str(&text);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Err(ImportError::Syntax(String::from(
+            "the file has no root element",
+        )))
+    }
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| &value[..])
+    }
+    fn number<T: std::str::FromStr>(&self, name: &str) -> Result<T, ImportError> {
+        let value = self
+            .attribute(name)
+            .ok_or_else(|| ImportError::Missing(format!("{} on <{}>", name, self.name)))?;
+        value.parse().map_err(|_| {
+            ImportError::Invalid(format!(
+                "{} on <{}> should be a number, not {}",
+                name, self.name, value
+            ))
+        })
+    }
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+    /// Custom properties as text, whatever type they were given in Tiled
+    fn properties(&self) -> BTreeMap<String, String> {
+        let mut properties = BTreeMap::new();
+        for property in self
+            .children("properties")
+            .flat_map(|p| p.children("property"))
+        {
+            if let Some(name) = property.attribute("name") {
+                // Multi-line strings are saved as the property's text rather than its value
+                let value = property
+                    .attribute("value")
+                    .map(String::from)
+                    .unwrap_or_else(|| property.text.clone());
+                properties.insert(name.to_string(), value);
+            }
+        }
+        properties
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world::level::load;
+
+    /// A map with no tiles or objects in it, for trying out what the importer refuses
+    const EMPTY_MAP: &str = r#"<map orientation="orthogonal" width="1" height="1" tilewidth="5" tileheight="5" infinite="0">
+ <tileset firstgid="1" name="tiles" tilewidth="5" tileheight="5" tilecount="16" columns="4"/>
+ <layer id="1" name="ground" width="1" height="1">
+  <data encoding="csv">0</data>
+ </layer>
+</map>"#;
+
+    #[test]
+    fn tmx_and_tmj_import_the_same() {
+        let tmx = load("maps/ruins.tmx").unwrap();
+        let tmj = load("maps/ruins.tmj").unwrap();
+        assert_eq!(tmx, tmj);
+    }
+
+    #[test]
+    fn layers_stack_into_tiles() {
+        let level = load("maps/ruins.tmx").unwrap();
+        assert_eq!(level.name, "Ruins");
+        assert_eq!(level.background, Rgb([0xa0, 0xc0, 0xe0]));
+        assert_eq!(level.tiles.len(), 5);
+        assert!(level.tiles.iter().all(|row| row.len() == 30));
+        assert_eq!(level.tiles[0][20], Some(5));
+        assert_eq!(level.tiles[3][0], Some(0));
+        assert_eq!(level.tiles[3][12], None);
+        assert_eq!(level.tiles[4][0], Some(13));
+        // The decoration layer draws over the ground, flipped or not
+        assert_eq!(level.tiles[3][25], Some(1));
+        assert_eq!(level.tiles[2][27], Some(14));
+        assert_eq!(level.passable, vec![14]);
+        assert!(!level.terrain().is_solid((3, 2)));
+        assert!(level.terrain().is_solid((3, 3)));
+    }
+
+    #[test]
+    fn objects_become_markers() {
+        let level = load("maps/ruins.tmj").unwrap();
+        assert_eq!(level.player_start, (10.0, 0.0).into());
+        assert_eq!(level.spawn("west"), Some((5.0, 5.0).into()));
+        assert_eq!(level.spawn("ledge"), Some((108.0, -10.0).into()));
+        assert_eq!(level.triggers.len(), 1);
+        let exit = &level.triggers[0];
+        assert_eq!(exit.name, "exit");
+        assert_eq!(exit.area, GameRect::new((140.0, 0.0).into(), (10.0, 15.0)));
+        assert_eq!(exit.properties["leads_to"], "plains");
+        assert_eq!(exit.properties["once"], "true");
+    }
+
+    #[test]
+    fn objects_scale_with_tile_size() {
+        let map = EMPTY_MAP
+            .replace(
+                r#"tilewidth="5" tileheight="5" infinite"#,
+                r#"tilewidth="10" tileheight="20" infinite"#,
+            )
+            .replace(
+                "</map>",
+                r#"<objectgroup><object type="player_start" x="30" y="40"/></objectgroup></map>"#,
+            );
+        let level = import_tmx(&map, "scaled").unwrap();
+        assert_eq!(level.name, "scaled");
+        assert_eq!(level.player_start, (15.0, 10.0).into());
+    }
+
+    #[test]
+    fn refuses_what_levels_cannot_hold() {
+        let with_start = |map: &str| {
+            map.replace(
+                "</map>",
+                r#"<objectgroup><object type="player_start" x="0" y="0"/></objectgroup></map>"#,
+            )
+        };
+        assert!(import_tmx(&with_start(EMPTY_MAP), "empty").is_ok());
+
+        let infinite = with_start(&EMPTY_MAP.replace(r#"infinite="0""#, r#"infinite="1""#));
+        assert!(matches!(
+            import_tmx(&infinite, "empty"),
+            Err(ImportError::Unsupported(_))
+        ));
+
+        let compressed = with_start(&EMPTY_MAP.replace(
+            r#"<data encoding="csv">0</data>"#,
+            r#"<data encoding="base64" compression="zlib">eJxjYAAAAAEAAQ==</data>"#,
+        ));
+        assert!(matches!(
+            import_tmx(&compressed, "empty"),
+            Err(ImportError::Unsupported(_))
+        ));
+
+        let isometric = with_start(&EMPTY_MAP.replace("orthogonal", "isometric"));
+        assert!(matches!(
+            import_tmx(&isometric, "empty"),
+            Err(ImportError::Unsupported(_))
+        ));
+
+        let external = with_start(&EMPTY_MAP.replace(r#"name="tiles" "#, r#"source="tiles.tsx" "#));
+        assert!(matches!(
+            import_tmx(&external, "empty"),
+            Err(ImportError::Unsupported(_))
+        ));
+
+        assert!(matches!(
+            import_tmx(EMPTY_MAP, "empty"),
+            Err(ImportError::Missing(_))
+        ));
+        assert!(matches!(
+            import_tmx("<map>", "empty"),
+            Err(ImportError::Syntax(_))
+        ));
+        assert!(matches!(
+            import_tmj("{\"width\": 1,", "empty"),
+            Err(ImportError::Syntax(_))
+        ));
+
+        let base64 = r#"{"width": 1, "height": 1, "tilewidth": 5, "tileheight": 5,
+            "tilesets": [{"firstgid": 1, "columns": 4, "tilecount": 16}],
+            "layers": [{"type": "tilelayer", "name": "ground", "encoding": "base64", "data": "AAAAAA=="}]}"#;
+        assert!(matches!(
+            import_tmj(base64, "empty"),
+            Err(ImportError::Unsupported(_))
+        ));
+    }
+    #[test]
+    fn backgrounds_drop_their_alpha() {
+        let with_background = |colour: &str| {
+            EMPTY_MAP.replace(
+                r#"infinite="0">"#,
+                &format!(r#"infinite="0" backgroundcolor="{}">"#, colour),
+            )
+            .replace(
+                "</map>",
+                r#"<objectgroup><object type="player_start" x="0" y="0"/></objectgroup></map>"#,
+            )
+        };
+        let level = import_tmx(&with_background("#80a0c0e0"), "empty").unwrap();
+        assert_eq!(level.background, Rgb([0xa0, 0xc0, 0xe0]));
+
+        for junk in ["#ä0c0e0", "#a0c0e0é", "#green"] {
+            assert!(matches!(
+                import_tmx(&with_background(junk), "empty"),
+                Err(ImportError::Invalid(_))
+            ));
+        }
+    }
+}
@@ -1 +1,87 @@
-pub mod space;
\ No newline at end of file
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::utility::clock::Clock;
+
+use self::{
+    collision::{Collider, ColliderKind, Collision, EntityId, GameRect},
+    terrain::TileGrid,
+};
+
+pub mod collision;
+pub mod difficulty;
+pub mod director;
+pub mod level;
+pub mod seed;
+pub mod space;
+pub mod terrain;
+
+/// Everything entities share while they update
+pub struct World {
+    pub clock: Clock,
+    /// Solid ground, which stays put from one step to the next
+    pub terrain: TileGrid,
+    /// Source of every random choice in the simulation, seeded so a run can be replayed
+    pub rng: StdRng,
+    next_id: u32,
+    colliders: Vec<Collider>,
+    collisions: Vec<Collision>,
+}
+
+impl World {
+    pub fn new(clock: Clock, seed: u64) -> World {
+        World {
+            clock,
+            terrain: TileGrid::new(),
+            rng: StdRng::seed_from_u64(seed),
+            next_id: 0,
+            colliders: Vec::new(),
+            collisions: Vec::new(),
+        }
+    }
+    pub fn next_id(&mut self) -> EntityId {
+        self.next_id += 1;
+        EntityId(self.next_id)
+    }
+    /// Forgets the last step's colliders and collisions, so entities can add their new ones as they update
+    pub fn begin_step(&mut self) {
+        self.colliders.clear();
+        self.collisions.clear();
+    }
+    pub fn add_collider(&mut self, owner: EntityId, kind: ColliderKind, rect: GameRect) {
+        self.colliders.push(Collider { owner, kind, rect });
+    }
+    pub fn colliders(&self) -> &[Collider] {
+        &self.colliders
+    }
+    /// Colliders of `kind` that overlap `rect`
+    pub fn overlapping(
+        &self,
+        rect: GameRect,
+        kind: ColliderKind,
+    ) -> impl Iterator<Item = &Collider> + '_ {
+        self.colliders
+            .iter()
+            .filter(move |c| c.kind == kind && c.rect.overlaps(&rect))
+    }
+    /// Finds every hitbox touching another entity's hurtbox,
+    /// once every entity has added its colliders for the step
+    pub fn resolve_collisions(&mut self) {
+        let mut collisions = Vec::new();
+        for hitbox in self.colliders.iter().filter(|c| c.kind == ColliderKind::Hitbox) {
+            for hurtbox in self.overlapping(hitbox.rect, ColliderKind::Hurtbox) {
+                let collision = Collision {
+                    attacker: hitbox.owner,
+                    target: hurtbox.owner,
+                };
+                if hurtbox.owner != hitbox.owner && !collisions.contains(&collision) {
+                    collisions.push(collision);
+                }
+            }
+        }
+        self.collisions = collisions;
+    }
+    /// Collisions found by the last `resolve_collisions`
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+}
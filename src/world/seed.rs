@@ -0,0 +1,15 @@
+use std::sync::OnceLock;
+
+static SEED: OnceLock<u64> = OnceLock::new();
+
+/// Makes every run use `seed`, so the same level and the same goblins come up each time
+///
+/// Has no effect once a run has started
+pub fn set_seed(seed: u64) {
+    let _ = SEED.set(seed);
+}
+
+/// Seed for a new run, which is the one set with `set_seed` or a fresh one each run
+pub fn run_seed() -> u64 {
+    SEED.get().copied().unwrap_or_else(rand::random)
+}
@@ -1,6 +1,6 @@
 use std::{ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign}};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct GamePos {
     pub x: f32,
     pub y: f32,
@@ -54,9 +54,9 @@ impl DivAssign<f32> for GamePos {
         *self = self.div(rhs);
     }
 }
-impl Into<(f32, f32)> for GamePos {
-    fn into(self) -> (f32, f32) {
-        (self.x, self.y)
+impl From<GamePos> for (f32, f32) {
+    fn from(val: GamePos) -> Self {
+        (val.x, val.y)
     }
 }
 impl From<(f32, f32)> for GamePos {
@@ -75,4 +75,8 @@ impl GamePos {
     pub fn abs(&self) -> GamePos {
         (self.x.abs(), self.y.abs()).into()
     }
+    /// The point `t` of the way from `self` to `other`
+    pub fn lerp(self, other: GamePos, t: f32) -> GamePos {
+        self + (other - self) * t
+    }
 }
\ No newline at end of file
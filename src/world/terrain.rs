@@ -0,0 +1,215 @@
+use std::collections::HashSet;
+
+use super::{collision::GameRect, space::GamePos};
+
+/// Width and height of a tile, in units
+pub const TILE_SIZE: f32 = 5.0;
+
+/// Overlaps smaller than this are treated as touching, so rounding doesn't snag entities on tile edges
+const EPSILON: f32 = 0.001;
+
+/// Which tiles of the world are solid, indexed by column and row with tile (0, 0) starting at the origin
+#[derive(Clone, Debug, Default)]
+pub struct TileGrid {
+    solid: HashSet<(i32, i32)>,
+}
+
+/// Where a move ended up after running into terrain
+#[derive(Clone, Copy, Debug)]
+pub struct Sweep {
+    /// How far the rectangle was asked to move
+    pub requested: GamePos,
+    /// How far the rectangle got
+    pub moved: GamePos,
+    /// Whether it was stopped by a wall, on either side
+    pub hit_x: bool,
+    /// Whether it was stopped by a ceiling or floor
+    pub hit_y: bool,
+}
+
+impl Sweep {
+    /// Whether it came down onto a floor
+    ///
+    /// Goes by the move asked for rather than the one made,
+    /// since resting on a floor can nudge the rectangle up by a rounding error
+    pub fn landed(&self) -> bool {
+        self.hit_y && self.requested.y > 0.0
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Axis {
+    X,
+    Y,
+}
+
+impl TileGrid {
+    pub fn new() -> TileGrid {
+        TileGrid::default()
+    }
+    pub fn set_solid(&mut self, cell: (i32, i32), solid: bool) {
+        if solid {
+            self.solid.insert(cell);
+        } else {
+            self.solid.remove(&cell);
+        }
+    }
+    pub fn is_solid(&self, cell: (i32, i32)) -> bool {
+        self.solid.contains(&cell)
+    }
+    /// Moves `rect` by `delta`, stopping against the first solid tile in the way
+    ///
+    /// Moves across then down, sweeping every tile passed through on the way,
+    /// so nothing tunnels through a tile however fast it goes
+    pub fn sweep(&self, rect: GameRect, delta: GamePos) -> Sweep {
+        let (dx, hit_x) = self.sweep_axis(rect, Axis::X, delta.x);
+        let across = GameRect {
+            min: (rect.min.x + dx, rect.min.y).into(),
+            max: (rect.max.x + dx, rect.max.y).into(),
+        };
+        let (dy, hit_y) = self.sweep_axis(across, Axis::Y, delta.y);
+        Sweep {
+            requested: delta,
+            moved: (dx, dy).into(),
+            hit_x,
+            hit_y,
+        }
+    }
+    /// How far `rect` gets moving `delta` along `axis`, and whether a tile stopped it
+    fn sweep_axis(&self, rect: GameRect, axis: Axis, delta: f32) -> (f32, bool) {
+        // `along` is the axis moved on and `across` the other, so one sweep serves both
+        let (min, max, across_min, across_max) = match axis {
+            Axis::X => (rect.min.x, rect.max.x, rect.min.y, rect.max.y),
+            Axis::Y => (rect.min.y, rect.max.y, rect.min.x, rect.max.x),
+        };
+        let cell = |along: i32, across: i32| match axis {
+            Axis::X => (along, across),
+            Axis::Y => (across, along),
+        };
+        let blocked = |along: i32| span(across_min, across_max).any(|across| self.is_solid(cell(along, across)));
+
+        if delta > 0.0 {
+            // Tiles whose near edge lies between the leading edge and where it ends up
+            let first = ((max - EPSILON) / TILE_SIZE).ceil() as i32;
+            let last = ((max + delta) / TILE_SIZE).ceil() as i32 - 1;
+            for along in first..=last {
+                if blocked(along) {
+                    return ((along as f32 * TILE_SIZE - max).min(delta), true);
+                }
+            }
+        } else if delta < 0.0 {
+            let first = ((min + EPSILON) / TILE_SIZE).floor() as i32 - 1;
+            let last = ((min + delta) / TILE_SIZE).floor() as i32;
+            for along in (last..=first).rev() {
+                if blocked(along) {
+                    return (((along + 1) as f32 * TILE_SIZE - min).max(delta), true);
+                }
+            }
+        }
+        (delta, false)
+    }
+}
+
+/// Tiles a span from `min` to `max` overlaps along one axis, ignoring ones it only just touches
+fn span(min: f32, max: f32) -> std::ops::Range<i32> {
+    let first = ((min + EPSILON) / TILE_SIZE).floor() as i32;
+    let last = ((max - EPSILON) / TILE_SIZE).ceil() as i32;
+    first..last
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A floor along row 2, and a one tile wall on it in column 4
+    fn grid() -> TileGrid {
+        let mut grid = TileGrid::new();
+        for column in -10..=10 {
+            grid.set_solid((column, 2), true);
+        }
+        grid.set_solid((4, 1), true);
+        grid
+    }
+
+    /// A tile sized box with its top left at `(x, y)`
+    fn tile_box(x: f32, y: f32) -> GameRect {
+        GameRect::new((x, y).into(), (TILE_SIZE, TILE_SIZE))
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 0.01
+    }
+
+    #[test]
+    fn lands_on_the_floor() {
+        let sweep = grid().sweep(tile_box(0.0, 2.0), (0.0, 10.0).into());
+        assert!(close(sweep.moved.y, 3.0));
+        assert!(sweep.hit_y && sweep.landed());
+    }
+
+    #[test]
+    fn stays_landed_while_resting_on_the_floor() {
+        let grid = grid();
+        let mut rect = tile_box(0.0, TILE_SIZE);
+        for _ in 0..10 {
+            let sweep = grid.sweep(rect, (0.0, 0.05).into());
+            assert!(sweep.landed());
+            rect.min += sweep.moved;
+            rect.max += sweep.moved;
+        }
+    }
+
+    #[test]
+    fn walls_stop_it_from_either_side() {
+        let grid = grid();
+        let from_left = grid.sweep(tile_box(10.0, TILE_SIZE), (8.0, 0.0).into());
+        assert!(close(from_left.moved.x, 5.0));
+        assert!(from_left.hit_x);
+
+        let from_right = grid.sweep(tile_box(30.0, TILE_SIZE), (-8.0, 0.0).into());
+        assert!(close(from_right.moved.x, -5.0));
+        assert!(from_right.hit_x);
+    }
+
+    #[test]
+    fn ceilings_stop_it_going_up() {
+        let sweep = grid().sweep(tile_box(20.0, 18.0), (0.0, -10.0).into());
+        assert!(close(sweep.moved.y, -3.0));
+        assert!(sweep.hit_y);
+        assert!(!sweep.landed());
+    }
+
+    #[test]
+    fn fast_moves_do_not_tunnel_through_walls() {
+        let sweep = grid().sweep(tile_box(0.0, TILE_SIZE), (1_000.0, 0.0).into());
+        assert!(close(sweep.moved.x, 15.0));
+        assert!(sweep.hit_x);
+    }
+
+    #[test]
+    fn falls_through_gaps() {
+        let mut grid = grid();
+        grid.set_solid((0, 2), false);
+        let sweep = grid.sweep(tile_box(0.0, TILE_SIZE), (0.0, 4.0).into());
+        assert!(close(sweep.moved.y, 4.0));
+        assert!(!sweep.hit_y);
+
+        // Only a box that fits the gap gets through
+        let wide = GameRect::new((0.0, TILE_SIZE).into(), (TILE_SIZE + 1.0, TILE_SIZE));
+        assert!(grid.sweep(wide, (0.0, 4.0).into()).hit_y);
+    }
+
+    #[test]
+    fn slides_along_surfaces_it_only_touches() {
+        let grid = grid();
+        // Running along the floor, touching it exactly
+        let sweep = grid.sweep(tile_box(-40.0, TILE_SIZE), (10.0, 0.0).into());
+        assert!(close(sweep.moved.x, 10.0));
+        assert!(!sweep.hit_x);
+
+        // Dropping down the face of the wall, touching it exactly
+        let sweep = grid.sweep(tile_box(15.0, -10.0), (0.0, 8.0).into());
+        assert!(close(sweep.moved.y, 8.0));
+        assert!(!sweep.hit_y);
+    }
+}